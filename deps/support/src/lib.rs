@@ -9,9 +9,12 @@
 use std::cmp;
 use std::fmt;
 use std::iter::FromIterator;
+use std::marker::PhantomData;
 use std::mem::align_of;
 use std::ops;
 use std::ops::Range;
+use std::simd::cmp::SimdOrd;
+use std::simd::num::SimdFloat;
 use std::simd::LaneCount;
 use std::simd::Mask;
 use std::simd::MaskElement;
@@ -19,6 +22,7 @@ use std::simd::Simd;
 use std::simd::SimdElement;
 use std::simd::SupportedLaneCount;
 use std::slice;
+use std::str::FromStr;
 
 use bytemuck::Pod;
 use bytemuck::Zeroable;
@@ -29,15 +33,42 @@ pub type Predicate<T> = dyn Fn(T) -> bool;
 pub trait IAdd = ops::Add<Self, Output = Self> + Sized;
 pub trait IBAnd = ops::BitAnd<Self, Output = Self> + Sized;
 pub trait IBOr = ops::BitOr<Self, Output = Self> + Sized;
+pub trait IBNot = ops::Not<Output = Self> + Sized;
 pub trait IBXor = ops::BitXor<Self, Output = Self> + Sized;
 pub trait IDisplay = fmt::Debug + fmt::Display;
 pub trait IShl = ops::Shl<usize, Output = Self> + Sized;
 pub trait ISlice = slice::SliceIndex<[Self], Output = Self> + Sized;
 
+pub trait IBottom: PartialOrd + Sized {
+    const BOTTOM: Self;
+}
+
 pub trait ICast<T> {
     fn cast(self) -> T;
 }
 
+pub trait IOne: Eq + PartialOrd + Sized {
+    const ONE: Self;
+}
+
+/// An associative binary operator with an identity element (i.e., a monoid) over `T`.
+pub trait IOperator<T> {
+    const KIND: OperatorKind;
+
+    fn combine(a: T, b: T) -> T;
+
+    fn identity() -> T;
+}
+
+/// Lane-wise extension of the operator `IOperator<T>` to `Simd<T, N>`.
+pub trait IOperatorSimd<T, const N: usize>: IOperator<T>
+where
+    T: SimdElement,
+    LaneCount<N>: SupportedLaneCount,
+{
+    fn combine_simd(a: Simd<T, N>, b: Simd<T, N>) -> Simd<T, N>;
+}
+
 pub trait ITop: PartialOrd + Sized {
     const TOP: Self;
 }
@@ -59,6 +90,17 @@ pub trait IScan {
             Ok(())
         }
     }
+
+    fn check_flags(n_in: usize, n_flags: usize) -> Result<(), String> {
+        if n_in != n_flags {
+            Err(format!(
+                "Expected segment head flags of length {} (the input length), found length {}",
+                n_in, n_flags
+            ))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 #[repr(u8)]
@@ -69,6 +111,84 @@ pub enum DoubleBufferMode {
     B,
 }
 
+#[repr(u32)]
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub enum OperatorKind {
+    #[default]
+    Add,
+    Mul,
+    Min,
+    Max,
+    BAnd,
+    BOr,
+    BXor,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub enum ScanKind {
+    #[default]
+    Exclusive,
+    Inclusive,
+}
+
+/// A floating point sum paired with the running compensation of its rounding error,
+/// for compensated (Kahan-Neumaier) scans.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Compensated<T> {
+    pub sum: T,
+    pub comp: T,
+}
+
+/// An element paired with its segment head flag, for segmented scans.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Flagged<T> {
+    pub flag: bool,
+    pub value: T,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct OpAdd;
+
+#[derive(Clone, Copy, Default)]
+pub struct OpMul;
+
+#[derive(Clone, Copy, Default)]
+pub struct OpMin;
+
+#[derive(Clone, Copy, Default)]
+pub struct OpMax;
+
+#[derive(Clone, Copy, Default)]
+pub struct OpBAnd;
+
+#[derive(Clone, Copy, Default)]
+pub struct OpBOr;
+
+#[derive(Clone, Copy, Default)]
+pub struct OpBXor;
+
+/// The compensated (Kahan-Neumaier) addition over `Compensated<T>`, which accumulates the rounding
+/// error of every addition of the sums into the compensation.
+/// Unlike Kahan summation, both operands may carry a compensation, so that the operator may be
+/// applied in any (e.g., parallel) order.
+#[derive(Clone, Copy, Default)]
+pub struct OpAddCompensated;
+
+/// The flagged operator transformation of `O` over `Flagged<T>`, which restarts the scan
+/// at every element with its segment head flag set.
+#[derive(Clone, Copy, Default)]
+pub struct OpSegmented<O> {
+    op: PhantomData<O>,
+}
+
+/// A seedable pseudo-random number generator (SplitMix64), for reproducible inputs of tests
+/// and benchmarks without external dependencies.
+#[derive(Clone, Copy, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
 /// Returns an aligned slice
 pub fn align<'a, T, U>(n: usize, p: *mut T) -> &'a mut [T] {
     let offset = p.align_offset(align_of::<U>());
@@ -133,6 +253,159 @@ where
     Ok(())
 }
 
+/// Copy the scan input from the source vector to the destination vector.
+/// For exclusive scans the input is shifted right by one and `identity` is written to index 0;
+/// for inclusive scans the input is copied unchanged.
+pub fn copy_scan_input<T>(
+    kind: ScanKind,
+    identity: T,
+    v_src: &[T],
+    v_dst: &mut [T],
+) -> Result<(), String>
+where
+    T: Copy,
+{
+    let n_src = v_src.len();
+    let n_dst = v_dst.len();
+    match kind {
+        ScanKind::Exclusive => {
+            v_dst[1..n_dst].copy_from_slice(&v_src[..(n_src - 1)]);
+            v_dst[0] = identity;
+        }
+        ScanKind::Inclusive => v_dst[..n_dst].copy_from_slice(&v_src[..n_src]),
+    }
+    Ok(())
+}
+
+/// Returns the segment head flags for `n` elements given the offsets of the segments.
+pub fn flags_from_offsets(n: usize, offsets: &[usize]) -> Result<Vec<bool>, String> {
+    let mut flags = alloc(n, false);
+    for &offset in offsets {
+        if offset >= n {
+            return Err(format!(
+                "Expected segment offset ({}) less than input length ({})",
+                offset, n
+            ));
+        }
+        flags[offset] = true;
+    }
+    Ok(flags)
+}
+
+/// Pair the scan input with the segment head flags in the destination vector, such that
+/// an inclusive scan using `OpSegmented` over the destination vector computes the segmented scan
+/// of type `kind`.
+/// For exclusive scans the input is shifted right by one within each segment and `identity` is
+/// written to the head of each segment.
+pub fn segment<T>(
+    kind: ScanKind,
+    identity: T,
+    flags: &[bool],
+    v_src: &[T],
+    v_dst: &mut [Flagged<T>],
+) -> Result<(), String>
+where
+    T: Copy,
+{
+    for (k, x) in v_dst.iter_mut().enumerate() {
+        let flag = flags[k];
+        let value = match kind {
+            ScanKind::Exclusive if flag || k == 0 => identity,
+            ScanKind::Exclusive => v_src[k - 1],
+            ScanKind::Inclusive => v_src[k],
+        };
+        *x = Flagged { flag, value };
+    }
+    Ok(())
+}
+
+/// Copy the values from the flagged source vector to the destination vector.
+pub fn unsegment<T>(v_src: &[Flagged<T>], v_dst: &mut [T]) -> Result<(), String>
+where
+    T: Copy,
+{
+    v_src
+        .iter()
+        .enumerate()
+        .for_each(|(i, x)| v_dst[i] = x.value);
+    Ok(())
+}
+
+/// Pair the scan input with a zero compensation in the destination vector, such that a scan
+/// using `OpAddCompensated` over the destination vector computes the compensated sum.
+pub fn compensate<T>(v_src: &[T], v_dst: &mut [Compensated<T>]) -> Result<(), String>
+where
+    T: Copy,
+    Compensated<T>: From<T>,
+{
+    v_src
+        .iter()
+        .enumerate()
+        .for_each(|(i, &x)| v_dst[i] = Compensated::from(x));
+    Ok(())
+}
+
+/// Copy the compensated sums from the source vector to the destination vector.
+pub fn uncompensate<T>(v_src: &[Compensated<T>], v_dst: &mut [T]) -> Result<(), String>
+where
+    T: Copy + From<Compensated<T>>,
+{
+    v_src
+        .iter()
+        .enumerate()
+        .for_each(|(i, &x)| v_dst[i] = T::from(x));
+    Ok(())
+}
+
+/// Compute the scan of type `kind` in pairwise order: leaves of up to `n_leaf` elements are
+/// scanned (inclusive) by `scan_leaf`, and adjacent blocks are merged by recursive doubling,
+/// combining the total of the left block into every element of the right block.
+/// For floating point operators, the rounding error of every output thus grows with
+/// `n_leaf + log2(n)` instead of `n`.
+pub fn scan_pairwise<T, O, F>(
+    kind: ScanKind,
+    n_leaf: usize,
+    v_in: &[T],
+    v_out: &mut [T],
+    mut scan_leaf: F,
+) -> Result<(), String>
+where
+    T: Copy,
+    O: IOperator<T>,
+    F: FnMut(&[T], &mut [T]) -> Result<(), String>,
+{
+    let n = v_out.len();
+    if v_in.len() != n {
+        return Err(format!(
+            "Expected output vector of length {} for input length {}",
+            n,
+            v_in.len()
+        ));
+    }
+    if n_leaf == 0 {
+        return Err("Expected non-zero leaf size".to_string());
+    }
+    for (leaf_in, leaf_out) in v_in.chunks(n_leaf).zip(v_out.chunks_mut(n_leaf)) {
+        scan_leaf(leaf_in, leaf_out)?;
+    }
+    let mut width = n_leaf;
+    while width < n {
+        for block in v_out.chunks_mut(2 * width) {
+            if block.len() > width {
+                let (left, right) = block.split_at_mut(width);
+                let total = left[width - 1];
+                right.iter_mut().for_each(|y| *y = O::combine(total, *y));
+            }
+        }
+        width *= 2;
+    }
+    if kind == ScanKind::Exclusive && n > 0 {
+        v_out.copy_within(0..(n - 1), 1);
+        v_out[0] = O::identity();
+    }
+    Ok(())
+}
+
 pub fn copy_casted<T, U>(v_src: &[T], v_dst: &mut [U]) -> Result<(), String>
 where
     T: Copy + ICast<U>,
@@ -261,7 +534,7 @@ pub fn rotate_right<T>(n: usize, v_src: &[T], v_dst: &mut [T]) -> Result<(), Str
 where
     T: Copy,
 {
-    v_dst[0] = v_src[n];
+    v_dst[0] = v_src[n - 1];
     v_dst[1..n].copy_from_slice(&v_src[0..(n - 1)]);
     Ok(())
 }
@@ -276,6 +549,10 @@ where
     T: Copy + SimdElement,
     LaneCount<N>: SupportedLaneCount,
 {
+    // NOTE: Single lanes cannot be rotated (nor strided by powers of `N`)
+    if N == 1 {
+        return rotate_right(n, v_src, v_dst);
+    }
     let n_rem = n - n / N;
     let last = v_src[n - 1]; // TODO: Per depth?
     let simd_def = Simd::<T, N>::from_array([def; N]);
@@ -464,15 +741,250 @@ where
     Mask::<U, N>::splat(true)
 }
 
-impl ICast<i32> for i64 {
-    fn cast(self) -> i32 {
-        self as i32
+impl ICast<i64> for i64 {
+    fn cast(self) -> i64 {
+        self
     }
 }
 
-impl ICast<i64> for i32 {
-    fn cast(self) -> i64 {
-        self as i64
+/// Casts between `T` and `i64`, sign- or zero-extending (resp. truncating) to the wider type.
+macro_rules! ImplCastI64 {
+    () => {};
+    ($T:ty) => {
+        impl ICast<i64> for $T {
+            fn cast(self) -> i64 {
+                self as i64
+            }
+        }
+
+        impl ICast<$T> for i64 {
+            fn cast(self) -> $T {
+                self as $T
+            }
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplCastI64!($T);
+        ImplCastI64!($($tail)*);
+    };
+}
+ImplCastI64!(i8, i16, i32, isize, u8, u16, u32, u64, usize);
+
+macro_rules! ImplBottomInt {
+    () => {};
+    ($T:ty) => {
+        impl IBottom for $T {
+            const BOTTOM: $T = <$T>::MIN;
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplBottomInt!($T);
+        ImplBottomInt!($($tail)*);
+    };
+}
+ImplBottomInt!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! ImplOneInt {
+    () => {};
+    ($T:ty) => {
+        impl IOne for $T {
+            const ONE: $T = 1 as $T;
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplOneInt!($T);
+        ImplOneInt!($($tail)*);
+    };
+}
+ImplOneInt!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! ImplOperator {
+    () => {};
+    (
+        (
+            $Op:ty,
+            $Kind:expr,
+            $T:ty,
+            |$a:ident, $b:ident| $Combine:expr,
+            |$a_simd:ident, $b_simd:ident| $CombineSimd:expr,
+            $Identity:expr$(,)?
+        )
+    ) => {
+        impl IOperator<$T> for $Op {
+            const KIND: OperatorKind = $Kind;
+
+            fn combine($a: $T, $b: $T) -> $T {
+                $Combine
+            }
+
+            fn identity() -> $T {
+                $Identity
+            }
+        }
+
+        impl<const N: usize> IOperatorSimd<$T, N> for $Op
+        where
+            LaneCount<N>: SupportedLaneCount,
+        {
+            fn combine_simd($a_simd: Simd<$T, N>, $b_simd: Simd<$T, N>) -> Simd<$T, N> {
+                $CombineSimd
+            }
+        }
+    };
+    (($($Args:tt)*), $($tail:tt)*) => {
+        ImplOperator!(($($Args)*));
+        ImplOperator!($($tail)*);
+    };
+}
+
+macro_rules! ImplOperatorInt {
+    () => {};
+    ($T:ty) => {
+        ImplOperator!(
+            // NOTE: Wrap on overflow, like the lane-wise SIMD (and GPU) operators
+            (
+                OpAdd,
+                OperatorKind::Add,
+                $T,
+                |a, b| a.wrapping_add(b),
+                |a, b| a + b,
+                <$T>::ZERO
+            ),
+            (
+                OpMul,
+                OperatorKind::Mul,
+                $T,
+                |a, b| a.wrapping_mul(b),
+                |a, b| a * b,
+                <$T>::ONE
+            ),
+            (
+                OpMin,
+                OperatorKind::Min,
+                $T,
+                |a, b| cmp::min(a, b),
+                |a, b| a.simd_min(b),
+                <$T>::TOP,
+            ),
+            (
+                OpMax,
+                OperatorKind::Max,
+                $T,
+                |a, b| cmp::max(a, b),
+                |a, b| a.simd_max(b),
+                <$T>::BOTTOM,
+            ),
+            (OpBAnd, OperatorKind::BAnd, $T, |a, b| a & b, |a, b| a & b, !<$T>::ZERO),
+            (OpBOr, OperatorKind::BOr, $T, |a, b| a | b, |a, b| a | b, <$T>::ZERO),
+            (OpBXor, OperatorKind::BXor, $T, |a, b| a ^ b, |a, b| a ^ b, <$T>::ZERO),
+        );
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplOperatorInt!($T);
+        ImplOperatorInt!($($tail)*);
+    };
+}
+ImplOperatorInt!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+// NOTE: The bitwise operators are not defined over floating point types
+macro_rules! ImplOperatorFloat {
+    () => {};
+    ($T:ty) => {
+        ImplOperator!(
+            (OpAdd, OperatorKind::Add, $T, |a, b| a + b, |a, b| a + b, 0.0),
+            (OpMul, OperatorKind::Mul, $T, |a, b| a * b, |a, b| a * b, 1.0),
+            (
+                OpMin,
+                OperatorKind::Min,
+                $T,
+                |a, b| a.min(b),
+                |a, b| a.simd_min(b),
+                <$T>::INFINITY,
+            ),
+            (
+                OpMax,
+                OperatorKind::Max,
+                $T,
+                |a, b| a.max(b),
+                |a, b| a.simd_max(b),
+                <$T>::NEG_INFINITY,
+            ),
+        );
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplOperatorFloat!($T);
+        ImplOperatorFloat!($($tail)*);
+    };
+}
+ImplOperatorFloat!(f32, f64);
+
+macro_rules! ImplOperatorCompensated {
+    () => {};
+    ($T:ty) => {
+        impl IOperator<Compensated<$T>> for OpAddCompensated {
+            const KIND: OperatorKind = OperatorKind::Add;
+
+            fn combine(a: Compensated<$T>, b: Compensated<$T>) -> Compensated<$T> {
+                let sum = a.sum + b.sum;
+                // NOTE: The rounding error is recovered from the larger summand (Neumaier)
+                let err = if a.sum.abs() >= b.sum.abs() {
+                    (a.sum - sum) + b.sum
+                } else {
+                    (b.sum - sum) + a.sum
+                };
+                Compensated {
+                    sum,
+                    comp: a.comp + b.comp + err,
+                }
+            }
+
+            fn identity() -> Compensated<$T> {
+                Compensated { sum: 0.0, comp: 0.0 }
+            }
+        }
+
+        impl From<$T> for Compensated<$T> {
+            fn from(x: $T) -> Self {
+                Compensated { sum: x, comp: 0.0 }
+            }
+        }
+
+        impl From<Compensated<$T>> for $T {
+            fn from(x: Compensated<$T>) -> Self {
+                x.sum + x.comp
+            }
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplOperatorCompensated!($T);
+        ImplOperatorCompensated!($($tail)*);
+    };
+}
+ImplOperatorCompensated!(f32, f64);
+
+impl<T, O> IOperator<Flagged<T>> for OpSegmented<O>
+where
+    T: Copy,
+    O: IOperator<T>,
+{
+    const KIND: OperatorKind = O::KIND;
+
+    fn combine(a: Flagged<T>, b: Flagged<T>) -> Flagged<T> {
+        Flagged {
+            flag: a.flag || b.flag,
+            value: if b.flag {
+                b.value
+            } else {
+                O::combine(a.value, b.value)
+            },
+        }
+    }
+
+    fn identity() -> Flagged<T> {
+        Flagged {
+            flag: false,
+            value: O::identity(),
+        }
     }
 }
 
@@ -517,6 +1029,30 @@ impl DoubleBufferMode {
     }
 }
 
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed integer in `[0, n)`, for `n > 0`.
+    pub fn next_below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    /// Returns a uniformly distributed float in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
 impl fmt::Display for DoubleBufferMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -533,3 +1069,92 @@ impl fmt::Display for DoubleBufferMode {
 unsafe impl Pod for DoubleBufferMode {}
 
 unsafe impl Zeroable for DoubleBufferMode {}
+
+impl<T> fmt::Display for Compensated<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}({:+})", self.sum, self.comp)
+    }
+}
+
+impl<T> fmt::Display for Flagged<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.flag {
+            write!(f, "|{}", self.value)
+        } else {
+            write!(f, "{}", self.value)
+        }
+    }
+}
+
+impl OperatorKind {
+    pub fn get_options_string() -> String {
+        format!(
+            "Operators:\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            OperatorKind::Add.to_option_string(),
+            OperatorKind::Mul.to_option_string(),
+            OperatorKind::Min.to_option_string(),
+            OperatorKind::Max.to_option_string(),
+            OperatorKind::BAnd.to_option_string(),
+            OperatorKind::BOr.to_option_string(),
+            OperatorKind::BXor.to_option_string(),
+        )
+    }
+
+    pub fn to_option_string(self) -> String {
+        format!("*  {}", self)
+    }
+}
+
+impl fmt::Display for OperatorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OperatorKind::Add => "add",
+                OperatorKind::Mul => "mul",
+                OperatorKind::Min => "min",
+                OperatorKind::Max => "max",
+                OperatorKind::BAnd => "band",
+                OperatorKind::BOr => "bor",
+                OperatorKind::BXor => "bxor",
+            }
+        )
+    }
+}
+
+impl FromStr for OperatorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "add" => Ok(OperatorKind::Add),
+            "mul" => Ok(OperatorKind::Mul),
+            "min" => Ok(OperatorKind::Min),
+            "max" => Ok(OperatorKind::Max),
+            "band" => Ok(OperatorKind::BAnd),
+            "bor" => Ok(OperatorKind::BOr),
+            "bxor" => Ok(OperatorKind::BXor),
+            _ => Err(format!("Invalid operator: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ScanKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ScanKind::Exclusive => "Exclusive",
+                ScanKind::Inclusive => "Inclusive",
+            }
+        )
+    }
+}
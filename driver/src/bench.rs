@@ -0,0 +1,147 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::time::Duration;
+
+use crate::element::ElementKind;
+use crate::element::IElement;
+use crate::options::Options;
+use crate::random;
+use crate::scan::ImplKind;
+
+/// The statistics of the timed invocations of an implementation over an input of length `n`.
+struct Stats {
+    impl_kind: ImplKind,
+    n: usize,
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    stddev: Duration,
+}
+
+impl Stats {
+    fn new(impl_kind: ImplKind, n: usize, timing: &[Duration]) -> Self {
+        let mut timing_sorted = timing.to_vec();
+        timing_sorted.sort();
+        let k = timing_sorted.len();
+        let median = if k % 2 == 0 {
+            (timing_sorted[k / 2 - 1] + timing_sorted[k / 2]) / 2
+        } else {
+            timing_sorted[k / 2]
+        };
+        let mean = timing.iter().map(Duration::as_secs_f64).sum::<f64>() / k as f64;
+        let var = match k {
+            1 => 0.0,
+            _ => {
+                timing
+                    .iter()
+                    .map(|t| (t.as_secs_f64() - mean).powi(2))
+                    .sum::<f64>()
+                    / (k - 1) as f64
+            }
+        };
+        Self {
+            impl_kind,
+            n,
+            min: timing_sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(var.sqrt()),
+        }
+    }
+
+    /// Returns the throughput at the median time of the invocations.
+    fn get_elements_per_sec(&self) -> f64 {
+        self.n as f64 / self.median.as_secs_f64()
+    }
+}
+
+/// Benchmark the implementation `impl_kind` (or all implementations, if `None`) for each of the
+/// input sizes, printing a comparison table of the implementations (per size) to stdout.
+pub fn run(impl_kind: Option<ImplKind>, options: &Options, verbose: bool) -> Result<(), String> {
+    match options.get_element(None)? {
+        ElementKind::I8 => run_element::<i8>(impl_kind, options, verbose),
+        ElementKind::I16 => run_element::<i16>(impl_kind, options, verbose),
+        ElementKind::I32 => run_element::<i32>(impl_kind, options, verbose),
+        ElementKind::I64 => run_element::<i64>(impl_kind, options, verbose),
+        ElementKind::Isize => run_element::<isize>(impl_kind, options, verbose),
+        ElementKind::U8 => run_element::<u8>(impl_kind, options, verbose),
+        ElementKind::U16 => run_element::<u16>(impl_kind, options, verbose),
+        ElementKind::U32 => run_element::<u32>(impl_kind, options, verbose),
+        ElementKind::U64 => run_element::<u64>(impl_kind, options, verbose),
+        ElementKind::Usize => run_element::<usize>(impl_kind, options, verbose),
+        ElementKind::F32 => run_element::<f32>(impl_kind, options, verbose),
+        ElementKind::F64 => run_element::<f64>(impl_kind, options, verbose),
+    }
+}
+
+fn run_element<T>(
+    impl_kind: Option<ImplKind>,
+    options: &Options,
+    verbose: bool,
+) -> Result<(), String>
+where
+    T: IElement,
+{
+    let impl_kinds: Vec<ImplKind> = match impl_kind {
+        Some(impl_kind) => vec![impl_kind],
+        None => ImplKind::ALL.to_vec(),
+    };
+    let n_warmup = options.get_warmup();
+    let n_repeat = options.get_repeat_bench();
+    // NOTE: The warm-up invocations (e.g., initializing the GPU context of the scan object) are
+    // run with the timed invocations on the same scan object, and their times discarded.
+    let options_bench = Options {
+        repeat: Some(n_warmup + n_repeat),
+        ..options.clone()
+    };
+    println!(
+        "{:<22} {:>10} {:>12} {:>12} {:>12} {:>12} {:>14} {:>8}",
+        "impl", "n", "min", "median", "mean", "stddev", "elem/s", "speedup"
+    );
+    for n in options.get_sizes() {
+        let (v_in, _backing_store_in) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+        let (v_out, _backing_store_out) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+        random::fill_random(options.get_seed(), options.get_dist(), v_in);
+        let flags = options.get_flags(n)?;
+
+        let mut stats_base: Option<Stats> = None;
+        for &impl_kind in impl_kinds.iter() {
+            let timing = match T::dispatch(
+                impl_kind,
+                &options_bench,
+                flags.as_deref(),
+                v_in,
+                v_out,
+                verbose,
+            ) {
+                Ok(timing) => timing,
+                Err(m) if impl_kinds.len() > 1 => {
+                    eprintln!("Skipped {} (N={}): {}", impl_kind, n, m);
+                    continue;
+                }
+                Err(m) => return Err(m),
+            };
+            let stats = Stats::new(impl_kind, n, &timing[n_warmup..]);
+            let speedup = match &stats_base {
+                Some(stats_base) => stats_base.median.as_secs_f64() / stats.median.as_secs_f64(),
+                None => 1.0,
+            };
+            println!(
+                "{:<22} {:>10} {:>12} {:>12} {:>12} {:>12} {:>14.4e} {:>7.2}x",
+                stats.impl_kind.to_string(),
+                stats.n,
+                format!("{:.3?}", stats.min),
+                format!("{:.3?}", stats.median),
+                format!("{:.3?}", stats.mean),
+                format!("{:.3?}", stats.stddev),
+                stats.get_elements_per_sec(),
+                speedup,
+            );
+            if stats_base.is_none() {
+                stats_base = Some(stats);
+            }
+        }
+    }
+    Ok(())
+}
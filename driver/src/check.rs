@@ -0,0 +1,91 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use crate::element::IElement;
+use crate::minimize;
+use crate::options::Options;
+use crate::scan::ImplKind;
+
+/// The implementation whose output is the reference of the check.
+const IMPL_REFERENCE: ImplKind = ImplKind::Sequential;
+
+/// Run every implementation over `v_in`, and compare its output with the output of the reference
+/// implementation, printing the first differing index of each implementation to stdout.
+/// Implementations which do not support the element type (see `ImplKind::supports`) or return
+/// `Unimplemented` are skipped, and any other error or mismatch fails the check. The input of
+/// each mismatch is minimised into a test-scan reproducer.
+pub fn run<T>(
+    options: &Options,
+    flags: Option<&[bool]>,
+    v_in: &[T],
+    verbose: bool,
+) -> Result<(), String>
+where
+    T: IElement,
+{
+    let n = v_in.len();
+    let options_check = Options {
+        repeat: None,
+        ..options.clone()
+    };
+    let (v_ref, _backing_store_ref) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+    let (v_out, _backing_store_out) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+    T::dispatch(IMPL_REFERENCE, &options_check, flags, v_in, v_ref, verbose)
+        .map_err(|m| format!("Failed reference {}: {}", IMPL_REFERENCE, m))?;
+    println!("{:<22} reference", IMPL_REFERENCE.to_string());
+
+    let mut n_failed = 0;
+    let mut mismatches: Vec<(ImplKind, usize)> = Vec::new();
+    for &impl_kind in ImplKind::ALL.iter().filter(|&&k| k != IMPL_REFERENCE) {
+        if !impl_kind.supports(T::KIND) {
+            println!(
+                "{:<22} skipped (Unsupported element type {})",
+                impl_kind.to_string(),
+                T::KIND
+            );
+            continue;
+        }
+        v_out.fill(T::zeroed());
+        let result = T::dispatch(impl_kind, &options_check, flags, v_in, v_out, verbose);
+        let status = match result {
+            Ok(_) => match get_first_mismatch(v_ref, v_out) {
+                None => "ok".to_string(),
+                Some(i) => {
+                    n_failed += 1;
+                    mismatches.push((impl_kind, i));
+                    format!(
+                        "mismatch at index {} (expected {}, found {})",
+                        i, v_ref[i], v_out[i]
+                    )
+                }
+            },
+            Err(m) if m == "Unimplemented" => "skipped (Unimplemented)".to_string(),
+            Err(m) => {
+                n_failed += 1;
+                format!("failed ({})", m)
+            }
+        };
+        println!("{:<22} {}", impl_kind.to_string(), status);
+    }
+
+    for (impl_kind, i) in mismatches {
+        match minimize::run(impl_kind, IMPL_REFERENCE, &options_check, flags, v_in, i) {
+            Ok(test) => println!("\nMinimised reproducer for {}:\n\n{}", impl_kind, test),
+            Err(m) => println!("\nFailed to minimise the input of {}: {}", impl_kind, m),
+        }
+    }
+
+    match n_failed {
+        0 => Ok(()),
+        _ => Err(format!(
+            "Found {} implementations disagreeing with {} (N={})",
+            n_failed, IMPL_REFERENCE, n
+        )),
+    }
+}
+
+/// Returns the first index at which the vectors differ, up to the rounding error of floating
+/// point element types (see `IElement::get_first_mismatch`).
+pub fn get_first_mismatch<T: IElement>(v_a: &[T], v_b: &[T]) -> Option<usize> {
+    T::get_first_mismatch(v_a, v_b)
+}
@@ -0,0 +1,558 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::fmt;
+use std::mem;
+use std::simd::LaneCount;
+use std::simd::MaskElement;
+use std::simd::SimdElement;
+use std::simd::SupportedLaneCount;
+use std::str::FromStr;
+use std::time::Duration;
+
+use bytemuck::Pod;
+use bytemuck::Zeroable;
+use support::Compensated;
+use support::IBottom;
+use support::ICast;
+use support::IDisplay;
+use support::IOperator;
+use support::IOperatorSimd;
+use support::IZero;
+use support::OpAdd;
+use support::OpAddCompensated;
+use support::OpBAnd;
+use support::OpBOr;
+use support::OpBXor;
+use support::OpMax;
+use support::OpMin;
+use support::OpMul;
+use support::OperatorKind;
+use support::ScanKind;
+
+use crate::minimize::IShrink;
+use crate::options::Options;
+use crate::random::IRandom;
+use crate::scan;
+use crate::scan::ImplKind;
+use crate::scan::ScanFn;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub enum ElementKind {
+    I8,
+    I16,
+    I32,
+    #[default]
+    I64,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    Usize,
+    F32,
+    F64,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub enum AccuracyKind {
+    #[default]
+    Plain,
+    Compensated,
+    Pairwise,
+}
+
+/// The numbers of SIMD lanes (resp. elements per chunk or tile) selectable at runtime, for which
+/// the implementations are monomorphised.
+pub const LANES: [usize; 7] = [1, 2, 4, 8, 16, 32, 64];
+
+/// The number of elements of the leaves of pairwise scans, scanned in plain order (as in the
+/// pairwise summation of NumPy).
+pub const PAIRWISE_LEAF: usize = 128;
+
+type DispatchFn<T> =
+    fn(ImplKind, &Options, Option<&[bool]>, &[T], &mut [T], bool) -> Result<Vec<Duration>, String>;
+
+/// An element type selectable at runtime, which dispatches the scan to the monomorphised
+/// instances of the implementations (and operators) supporting it.
+pub trait IElement: Copy + IDisplay + FromStr + IRandom + IShrink + PartialOrd + Zeroable {
+    const KIND: ElementKind;
+
+    /// Decode an element from its little-endian bytes (of length `size_of::<Self>()`).
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+
+    /// Encode the element as its little-endian bytes (of length `size_of::<Self>()`).
+    fn write_le_bytes(self, bytes: &mut [u8]);
+
+    /// Returns the first index at which the scan outputs `v_a` and `v_b` differ: exactly for
+    /// integer types, or beyond the rounding error of reordered operations for floating point
+    /// types (i.e., `n` units in the last place of the largest magnitude of the scan so far,
+    /// where NaN elements compare equal).
+    fn get_first_mismatch(v_a: &[Self], v_b: &[Self]) -> Option<usize>;
+
+    fn dispatch(
+        impl_kind: ImplKind,
+        options: &Options,
+        flags: Option<&[bool]>,
+        v_in: &[Self],
+        v_out: &mut [Self],
+        verbose: bool,
+    ) -> Result<Vec<Duration>, String>;
+}
+
+fn dispatch_int<T, O, const N: usize>(
+    impl_kind: ImplKind,
+    options: &Options,
+    flags: Option<&[bool]>,
+    v_in: &[T],
+    v_out: &mut [T],
+    verbose: bool,
+) -> Result<Vec<Duration>, String>
+where
+    T: IElement + IBottom + ICast<i64> + IZero + Pod + Send + Sync + SimdElement,
+    T::Mask: IDisplay + MaskElement,
+    O: IOperatorSimd<T, N>,
+    LaneCount<N>: SupportedLaneCount,
+    i64: ICast<T>,
+{
+    let kind = options.kind;
+    let n_repeat = options.get_repeat();
+    if let Some(flags) = flags {
+        impl_kind.dispatch_segmented::<T, O, N>(kind, flags, n_repeat, v_in, v_out, verbose)
+    } else if impl_kind.is_parallel() {
+        impl_kind.dispatch_parallel::<T, O, N>(
+            kind,
+            options.threads,
+            n_repeat,
+            v_in,
+            v_out,
+            verbose,
+        )
+    } else if impl_kind.is_simd() {
+        impl_kind.dispatch_simd::<T, O, N>(kind, n_repeat, v_in, v_out, verbose)
+    } else {
+        impl_kind.dispatch::<T, O>(kind, n_repeat, v_in, v_out, verbose)
+    }
+}
+
+fn dispatch_float<T, O, const N: usize>(
+    impl_kind: ImplKind,
+    options: &Options,
+    flags: Option<&[bool]>,
+    v_in: &[T],
+    v_out: &mut [T],
+    verbose: bool,
+) -> Result<Vec<Duration>, String>
+where
+    T: IElement + Send + Sync + SimdElement,
+    T::Mask: IDisplay + MaskElement,
+    O: IOperatorSimd<T, N>,
+    LaneCount<N>: SupportedLaneCount,
+{
+    let kind = options.kind;
+    let n_repeat = options.get_repeat();
+    if let Some(flags) = flags {
+        impl_kind.dispatch_segmented::<T, O, N>(kind, flags, n_repeat, v_in, v_out, verbose)
+    } else if impl_kind.is_parallel() {
+        impl_kind.dispatch_parallel_cpu::<T, O, N>(
+            kind,
+            options.threads,
+            n_repeat,
+            v_in,
+            v_out,
+            verbose,
+        )
+    } else if impl_kind.is_simd() {
+        impl_kind.dispatch_simd::<T, O, N>(kind, n_repeat, v_in, v_out, verbose)
+    } else {
+        impl_kind.dispatch::<T, O>(kind, n_repeat, v_in, v_out, verbose)
+    }
+}
+
+/// Scan the floating point input as compensated sums (see `OpAddCompensated`), using the
+/// implementations taking any element type.
+fn dispatch_compensated<T, O, const N: usize>(
+    impl_kind: ImplKind,
+    options: &Options,
+    flags: Option<&[bool]>,
+    v_in: &[T],
+    v_out: &mut [T],
+    verbose: bool,
+) -> Result<Vec<Duration>, String>
+where
+    T: IElement + From<Compensated<T>> + Send + Sync,
+    Compensated<T>: From<T>,
+    O: IOperator<Compensated<T>>,
+{
+    if impl_kind.is_simd() {
+        return Err(format!(
+            "Expected non-SIMD implementation for accuracy mode {}",
+            AccuracyKind::Compensated
+        ));
+    }
+    let kind = options.kind;
+    let n_repeat = options.get_repeat();
+    let mut v_in_comp = support::alloc(v_in.len(), O::identity());
+    let mut v_out_comp = support::alloc(v_out.len(), O::identity());
+    support::compensate(v_in, &mut v_in_comp)?;
+    let timing = if let Some(flags) = flags {
+        impl_kind.dispatch_segmented::<Compensated<T>, O, N>(
+            kind,
+            flags,
+            n_repeat,
+            &v_in_comp,
+            &mut v_out_comp,
+            verbose,
+        )?
+    } else if impl_kind.is_parallel() {
+        impl_kind.dispatch_parallel_cpu::<Compensated<T>, O, N>(
+            kind,
+            options.threads,
+            n_repeat,
+            &v_in_comp,
+            &mut v_out_comp,
+            verbose,
+        )?
+    } else {
+        impl_kind.dispatch::<Compensated<T>, O>(
+            kind,
+            n_repeat,
+            &v_in_comp,
+            &mut v_out_comp,
+            verbose,
+        )?
+    };
+    support::uncompensate(&v_out_comp, v_out)?;
+    Ok(timing)
+}
+
+/// Scan the floating point input in pairwise order (see `support::scan_pairwise`), using the
+/// selected implementation for the leaves.
+fn dispatch_pairwise<T, O, const N: usize>(
+    impl_kind: ImplKind,
+    options: &Options,
+    flags: Option<&[bool]>,
+    v_in: &[T],
+    v_out: &mut [T],
+    verbose: bool,
+) -> Result<Vec<Duration>, String>
+where
+    T: IElement + Send + Sync + SimdElement,
+    T::Mask: IDisplay + MaskElement,
+    O: IOperatorSimd<T, N>,
+    LaneCount<N>: SupportedLaneCount,
+{
+    if flags.is_some() {
+        return Err(format!(
+            "Expected unsegmented scan for accuracy mode {}",
+            AccuracyKind::Pairwise
+        ));
+    }
+    // NOTE: Build the scan object once, and scan every leaf with it
+    let kind_leaf = ScanKind::Inclusive;
+    let run = |scan_leaf: &mut ScanFn<'_, T>| {
+        scan::repeat(options.get_repeat(), || {
+            support::scan_pairwise::<T, O, _>(
+                options.kind,
+                PAIRWISE_LEAF,
+                v_in,
+                v_out,
+                &mut *scan_leaf,
+            )
+        })
+    };
+    if impl_kind.is_parallel() {
+        impl_kind.with_scan_parallel_cpu::<T, O, N, _, _>(kind_leaf, options.threads, verbose, run)
+    } else if impl_kind.is_simd() {
+        impl_kind.with_scan_simd::<T, O, N, _, _>(kind_leaf, verbose, run)
+    } else {
+        impl_kind.with_scan::<T, O, _, _>(kind_leaf, verbose, run)
+    }
+}
+
+/// Selects the instance of the dispatch function `$f` monomorphised for `$T`, `$Op` and the
+/// number of lanes `$lanes` (see `LANES`).
+macro_rules! DispatchTable {
+    ($f:ident, $T:ty, $Op:ty, $lanes:expr) => {
+        match $lanes {
+            1 => Ok($f::<$T, $Op, 1> as DispatchFn<$T>),
+            2 => Ok($f::<$T, $Op, 2> as DispatchFn<$T>),
+            4 => Ok($f::<$T, $Op, 4> as DispatchFn<$T>),
+            8 => Ok($f::<$T, $Op, 8> as DispatchFn<$T>),
+            16 => Ok($f::<$T, $Op, 16> as DispatchFn<$T>),
+            32 => Ok($f::<$T, $Op, 32> as DispatchFn<$T>),
+            64 => Ok($f::<$T, $Op, 64> as DispatchFn<$T>),
+            n => Err(format!("Unsupported number of SIMD lanes: {}", n)),
+        }
+    };
+}
+
+macro_rules! ImplElementInt {
+    () => {};
+    (($T:ty, $Kind:expr)) => {
+        impl IElement for $T {
+            const KIND: ElementKind = $Kind;
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                let mut a = [0u8; mem::size_of::<$T>()];
+                a.copy_from_slice(bytes);
+                <$T>::from_le_bytes(a)
+            }
+
+            fn write_le_bytes(self, bytes: &mut [u8]) {
+                bytes.copy_from_slice(&<$T>::to_le_bytes(self));
+            }
+
+            fn get_first_mismatch(v_a: &[Self], v_b: &[Self]) -> Option<usize> {
+                v_a.iter().zip(v_b.iter()).position(|(a, b)| a != b)
+            }
+
+            fn dispatch(
+                impl_kind: ImplKind,
+                options: &Options,
+                flags: Option<&[bool]>,
+                v_in: &[Self],
+                v_out: &mut [Self],
+                verbose: bool,
+            ) -> Result<Vec<Duration>, String> {
+                if options.accuracy != AccuracyKind::Plain {
+                    return Err(format!(
+                        "Expected floating point element type for accuracy mode {}",
+                        options.accuracy
+                    ));
+                }
+                let lanes = options.get_lanes();
+                let f: DispatchFn<$T> = match options.op {
+                    OperatorKind::Add => DispatchTable!(dispatch_int, $T, OpAdd, lanes)?,
+                    OperatorKind::Mul => DispatchTable!(dispatch_int, $T, OpMul, lanes)?,
+                    OperatorKind::Min => DispatchTable!(dispatch_int, $T, OpMin, lanes)?,
+                    OperatorKind::Max => DispatchTable!(dispatch_int, $T, OpMax, lanes)?,
+                    OperatorKind::BAnd => DispatchTable!(dispatch_int, $T, OpBAnd, lanes)?,
+                    OperatorKind::BOr => DispatchTable!(dispatch_int, $T, OpBOr, lanes)?,
+                    OperatorKind::BXor => DispatchTable!(dispatch_int, $T, OpBXor, lanes)?,
+                };
+                f(impl_kind, options, flags, v_in, v_out, verbose)
+            }
+        }
+    };
+    (($($Args:tt)*), $($tail:tt)*) => {
+        ImplElementInt!(($($Args)*));
+        ImplElementInt!($($tail)*);
+    };
+}
+ImplElementInt!(
+    (i8, ElementKind::I8),
+    (i16, ElementKind::I16),
+    (i32, ElementKind::I32),
+    (i64, ElementKind::I64),
+    (isize, ElementKind::Isize),
+    (u8, ElementKind::U8),
+    (u16, ElementKind::U16),
+    (u32, ElementKind::U32),
+    (u64, ElementKind::U64),
+    (usize, ElementKind::Usize),
+);
+
+macro_rules! ImplElementFloat {
+    () => {};
+    (($T:ty, $Kind:expr)) => {
+        impl IElement for $T {
+            const KIND: ElementKind = $Kind;
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                let mut a = [0u8; mem::size_of::<$T>()];
+                a.copy_from_slice(bytes);
+                <$T>::from_le_bytes(a)
+            }
+
+            fn write_le_bytes(self, bytes: &mut [u8]) {
+                bytes.copy_from_slice(&<$T>::to_le_bytes(self));
+            }
+
+            fn get_first_mismatch(v_a: &[Self], v_b: &[Self]) -> Option<usize> {
+                // NOTE: Allow the rounding error of reordered sums (resp. products) of up to `n`
+                // elements, i.e., `n` units in the last place of the largest partial result so far.
+                // Infinite and NaN elements only match themselves (resp. any NaN element).
+                let tolerance = v_a.len() as $T * <$T>::EPSILON;
+                let mut scale: $T = 0.0;
+                v_a.iter().zip(v_b.iter()).position(|(&a, &b)| {
+                    if a.is_finite() && b.is_finite() {
+                        scale = scale.max(a.abs()).max(b.abs());
+                        (a - b).abs() > tolerance * scale
+                    } else {
+                        a != b && !(a.is_nan() && b.is_nan())
+                    }
+                })
+            }
+
+            fn dispatch(
+                impl_kind: ImplKind,
+                options: &Options,
+                flags: Option<&[bool]>,
+                v_in: &[Self],
+                v_out: &mut [Self],
+                verbose: bool,
+            ) -> Result<Vec<Duration>, String> {
+                let lanes = options.get_lanes();
+                let f: DispatchFn<$T> = match (options.accuracy, options.op) {
+                    (_, OperatorKind::BAnd | OperatorKind::BOr | OperatorKind::BXor) => {
+                        return Err(format!(
+                            "Expected integer element type for operator {}",
+                            options.op
+                        ))
+                    }
+                    (AccuracyKind::Plain, OperatorKind::Add) => {
+                        DispatchTable!(dispatch_float, $T, OpAdd, lanes)?
+                    }
+                    (AccuracyKind::Plain, OperatorKind::Mul) => {
+                        DispatchTable!(dispatch_float, $T, OpMul, lanes)?
+                    }
+                    (AccuracyKind::Plain, OperatorKind::Min) => {
+                        DispatchTable!(dispatch_float, $T, OpMin, lanes)?
+                    }
+                    (AccuracyKind::Plain, OperatorKind::Max) => {
+                        DispatchTable!(dispatch_float, $T, OpMax, lanes)?
+                    }
+                    (AccuracyKind::Compensated, OperatorKind::Add) => {
+                        DispatchTable!(dispatch_compensated, $T, OpAddCompensated, lanes)?
+                    }
+                    (AccuracyKind::Pairwise, OperatorKind::Add) => {
+                        DispatchTable!(dispatch_pairwise, $T, OpAdd, lanes)?
+                    }
+                    (AccuracyKind::Pairwise, OperatorKind::Mul) => {
+                        DispatchTable!(dispatch_pairwise, $T, OpMul, lanes)?
+                    }
+                    (accuracy, op) => {
+                        return Err(format!(
+                            "Unsupported operator {} for accuracy mode {}",
+                            op, accuracy
+                        ))
+                    }
+                };
+                f(impl_kind, options, flags, v_in, v_out, verbose)
+            }
+        }
+    };
+    (($($Args:tt)*), $($tail:tt)*) => {
+        ImplElementFloat!(($($Args)*));
+        ImplElementFloat!($($tail)*);
+    };
+}
+ImplElementFloat!((f32, ElementKind::F32), (f64, ElementKind::F64));
+
+impl AccuracyKind {
+    pub fn get_options_string() -> String {
+        format!(
+            "Accuracy modes:\n{}\n{}\n{}",
+            AccuracyKind::Plain.to_option_string(),
+            AccuracyKind::Compensated.to_option_string(),
+            AccuracyKind::Pairwise.to_option_string(),
+        )
+    }
+
+    pub fn to_option_string(self) -> String {
+        format!("*  {}", self)
+    }
+}
+
+impl ElementKind {
+    pub fn is_float(self) -> bool {
+        matches!(self, ElementKind::F32 | ElementKind::F64)
+    }
+
+    pub fn get_options_string() -> String {
+        format!(
+            "Element types:\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            ElementKind::I8.to_option_string(),
+            ElementKind::I16.to_option_string(),
+            ElementKind::I32.to_option_string(),
+            ElementKind::I64.to_option_string(),
+            ElementKind::Isize.to_option_string(),
+            ElementKind::U8.to_option_string(),
+            ElementKind::U16.to_option_string(),
+            ElementKind::U32.to_option_string(),
+            ElementKind::U64.to_option_string(),
+            ElementKind::Usize.to_option_string(),
+            ElementKind::F32.to_option_string(),
+            ElementKind::F64.to_option_string(),
+        )
+    }
+
+    pub fn to_option_string(self) -> String {
+        format!("*  {}", self)
+    }
+}
+
+impl fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ElementKind::I8 => "i8",
+                ElementKind::I16 => "i16",
+                ElementKind::I32 => "i32",
+                ElementKind::I64 => "i64",
+                ElementKind::Isize => "isize",
+                ElementKind::U8 => "u8",
+                ElementKind::U16 => "u16",
+                ElementKind::U32 => "u32",
+                ElementKind::U64 => "u64",
+                ElementKind::Usize => "usize",
+                ElementKind::F32 => "f32",
+                ElementKind::F64 => "f64",
+            }
+        )
+    }
+}
+
+impl FromStr for ElementKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "i8" => Ok(ElementKind::I8),
+            "i16" => Ok(ElementKind::I16),
+            "i32" => Ok(ElementKind::I32),
+            "i64" => Ok(ElementKind::I64),
+            "isize" => Ok(ElementKind::Isize),
+            "u8" => Ok(ElementKind::U8),
+            "u16" => Ok(ElementKind::U16),
+            "u32" => Ok(ElementKind::U32),
+            "u64" => Ok(ElementKind::U64),
+            "usize" => Ok(ElementKind::Usize),
+            "f32" => Ok(ElementKind::F32),
+            "f64" => Ok(ElementKind::F64),
+            _ => Err(format!("Invalid element type: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for AccuracyKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AccuracyKind::Plain => "plain",
+                AccuracyKind::Compensated => "compensated",
+                AccuracyKind::Pairwise => "pairwise",
+            }
+        )
+    }
+}
+
+impl FromStr for AccuracyKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(AccuracyKind::Plain),
+            "compensated" => Ok(AccuracyKind::Compensated),
+            "pairwise" => Ok(AccuracyKind::Pairwise),
+            _ => Err(format!("Invalid accuracy mode: {}", s)),
+        }
+    }
+}
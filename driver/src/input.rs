@@ -0,0 +1,129 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::mem;
+
+use crate::element::ElementKind;
+use crate::element::IElement;
+use crate::npy;
+use crate::random;
+use crate::random::DistKind;
+
+/// The source of the input vector of the scan.
+pub enum Input {
+    /// Positional command line arguments (one element per argument).
+    Args(Vec<String>),
+    /// Text of whitespace-, newline- or comma-separated elements.
+    Text(String),
+    /// Raw little-endian elements, whose number is inferred from the size of the data.
+    Binary(Vec<u8>),
+    /// Little-endian elements of a 1-D `.npy` array of the given element type.
+    Npy(ElementKind, Vec<u8>),
+    /// Random elements of the given length, seed and distribution (see `random::IRandom`).
+    Random(usize, u64, DistKind),
+}
+
+impl Input {
+    /// Read the input from the file at `path` (or from stdin, if `path` is `-`).
+    /// Files in the `.npy` format are detected from their contents.
+    pub fn read(path: &str, is_binary: bool) -> Result<Self, String> {
+        let mut bytes: Vec<u8> = Vec::new();
+        let result = match path {
+            "-" => io::stdin().read_to_end(&mut bytes),
+            _ => fs::File::open(path).and_then(|mut f| f.read_to_end(&mut bytes)),
+        };
+        if let Err(e) = result {
+            return Err(format!("Failed to read input from {}: {}", path, e));
+        }
+        if npy::is_npy(&bytes) {
+            let (kind, data) = npy::read(bytes)?;
+            Ok(Self::Npy(kind, data))
+        } else if is_binary {
+            Ok(Self::Binary(bytes))
+        } else {
+            match String::from_utf8(bytes) {
+                Ok(s) => Ok(Self::Text(s)),
+                Err(_) => Err(format!("Expected UTF-8 text input from {}", path)),
+            }
+        }
+    }
+
+    /// Returns the element type of the input, if it is fixed by the input format.
+    pub fn get_element(&self) -> Option<ElementKind> {
+        match self {
+            Self::Npy(kind, _) => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of elements of type `T` in the input.
+    pub fn len<T: IElement>(&self) -> Result<usize, String> {
+        match self {
+            Self::Args(args) => Ok(args.len()),
+            Self::Text(s) => Ok(split_text(s).count()),
+            Self::Random(n, _, _) => Ok(*n),
+            Self::Npy(kind, _) if *kind != T::KIND => Err(format!(
+                "Expected element type {} of .npy input (found {})",
+                kind,
+                T::KIND
+            )),
+            Self::Binary(bytes) | Self::Npy(_, bytes) => {
+                let size = mem::size_of::<T>();
+                if bytes.len() % size == 0 {
+                    Ok(bytes.len() / size)
+                } else {
+                    Err(format!(
+                        "Expected binary input size ({} bytes) to be a multiple of the size of {} \
+                        ({} bytes)",
+                        bytes.len(),
+                        T::KIND,
+                        size
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Fill `v` (of length `self.len::<T>()`) with the elements of the input.
+    pub fn fill<T: IElement>(&self, v: &mut [T]) -> Result<(), String> {
+        match self {
+            Self::Args(args) => fill_parsed(args.iter().map(String::as_str), "argument", v),
+            Self::Text(s) => fill_parsed(split_text(s), "input", v),
+            Self::Random(_, seed, dist) => {
+                random::fill_random(*seed, *dist, v);
+                Ok(())
+            }
+            Self::Binary(bytes) | Self::Npy(_, bytes) => {
+                let chunks = bytes.chunks_exact(mem::size_of::<T>());
+                v.iter_mut()
+                    .zip(chunks)
+                    .for_each(|(x, b)| *x = T::from_le_bytes(b));
+                Ok(())
+            }
+        }
+    }
+}
+
+fn fill_parsed<'a, T: IElement>(
+    tokens: impl Iterator<Item = &'a str>,
+    source: &str,
+    v: &mut [T],
+) -> Result<(), String> {
+    for (x, s) in v.iter_mut().zip(tokens) {
+        *x = s.parse::<T>().or(Err(format!(
+            "Failed to parse {} from {}: {}",
+            T::KIND,
+            source,
+            s
+        )))?;
+    }
+    Ok(())
+}
+
+fn split_text(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+}
@@ -3,106 +3,308 @@
 
 #![feature(portable_simd)]
 
+mod bench;
+mod check;
+mod element;
 mod exit;
+mod input;
+mod minimize;
+mod npy;
+mod options;
+mod output;
+mod random;
 mod scan;
 
-use std::cmp;
 use std::env;
-use std::str::FromStr;
+use std::fs;
+use std::io;
+use std::time::Duration;
 
+use support::OperatorKind;
+
+use crate::element::AccuracyKind;
+use crate::element::ElementKind;
+use crate::element::IElement;
 use crate::exit::exit;
 use crate::exit::ExitCode;
+use crate::input::Input;
+use crate::options::Options;
+use crate::output::FormatKind;
+use crate::output::Output;
+use crate::random::DistKind;
 
-const USAGE: &str = "usage: scan <Impl:i8> <N:i64> [<x_0:i64> .. <x_{N-1}:i64>]";
-
-type TInt = i64;
+const USAGE: &str = "usage: scan [<Options>] <Impl:i8> <N:i64> [<x_0:Type> .. <x_{N-1}:Type>]
+       scan [<Options>] --input=<Path> <Impl:i8>
+       scan [<Options>] --random=<N:usize> <Impl:i8>
+       scan check [<Options>] <N:i64> [<x_0:Type> .. <x_{N-1}:Type>]
+       scan check [<Options>] (--input=<Path> | --random=<N:usize>)
+       scan bench [<Options>] [<Impl:i8>]
+Options:
+*  --input=<Path>                   Read the input vector from a file (or stdin, if `-`) of
+                                    whitespace-, newline- or comma-separated elements
+*  --binary                         Read the input file as raw little-endian elements, inferring
+                                    N from its size (`.npy` files are detected from their
+                                    contents, and their dtype selects the element type)
+*  --random=<N:usize>               Generate a random input vector of length N
+*  --seed=<S:u64>                   Seed of the random input vector (default: 0)
+*  --dist=<Dist>                    Distribution of the random input vector (default: uniform)
+*  --output=<Path>                  Write the output vector to a `.npy` file (or stdout, if `-`)
+*  --format=<Fmt>                   Format of the results written to stdout (default: text, or
+                                    none if writing to an --output file)
+*  --type=<Type>                    Element type of the scan (default: i64)
+*  --accuracy=<Acc>                 Accuracy mode of floating point scans (default: plain)
+*  --inclusive                      Compute the inclusive scan (default: exclusive)
+*  --op=<Op>                        Scan operator (default: add)
+*  --flags=<f_0:0|1>,..             Segment head flags of the segmented scan
+*  --offsets=<o_0:usize>,..         Segment offsets of the segmented scan
+*  --lanes=<L:usize>                Number of SIMD lanes (resp. elements per chunk or tile) of
+                                    the SIMD (resp. parallel) implementations, one of
+                                    1, 2, 4, 8, 16, 32 or 64 (default: 4)
+*  --threads=<K:usize>              Number of worker threads of the parallel CPU
+                                    implementations (default: available parallelism)
+*  --repeat=<R:usize>               Run the scan R times with the same scan object,
+                                    reporting the elapsed time (default: 1, or 10 timed
+                                    invocations for bench)
+Bench options (for all implementations, if Impl is not given):
+*  --sizes=<n_0:usize>,..           Input sizes of the random input vectors (see --seed and
+                                    --dist) (default: 1024,65536,1048576)
+*  --warmup=<W:usize>               Number of untimed invocations before the timed invocations,
+                                    e.g., initializing the GPU context (default: 3)";
 
-fn parse_vec<T>(str_vec: &[String], default: T) -> Result<Vec<T>, &str>
+/// Read the input vector as elements of type `T` and run the scan over them (or check all
+/// implementations against the reference implementation, if `impl_kind` is `None`).
+fn run<T>(
+    impl_kind: Option<scan::ImplKind>,
+    options: &Options,
+    input: &Input,
+    verbose: bool,
+) -> Result<(), String>
 where
-    T: Copy + Eq + FromStr,
+    T: IElement,
 {
-    let res = str_vec
-        .iter()
-        .map(|s| s.parse::<T>().unwrap_or(default))
-        .collect::<Vec<T>>();
-    if res.contains(&default) {
-        Err("Failed to parse integer(s) from arguments")
-    } else {
-        Ok(res)
+    let n = input.len::<T>()?;
+
+    if verbose {
+        eprintln!("Found array length N = {}", n);
+    }
+
+    if n == 0 {
+        exit(ExitCode::Ok, Some("Empty array (N=0)"));
+    }
+
+    let flags = options.get_flags(n)?;
+
+    if verbose {
+        if let Some(flags) = &flags {
+            eprintln!("Found segment head flags: {:?}", flags);
+        }
+    }
+
+    let (v_in, _backing_store_in) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+    let (v_out, _backing_store_out) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+
+    input.fill(v_in)?;
+
+    if verbose {
+        eprintln!("Found input vector: {:?}", v_in);
+    }
+
+    let impl_kind = match impl_kind {
+        Some(impl_kind) => impl_kind,
+        None => return check::run(options, flags.as_deref(), v_in, verbose),
+    };
+
+    let timing = T::dispatch(impl_kind, options, flags.as_deref(), v_in, v_out, verbose)?;
+
+    if timing.len() > 1 {
+        let elapsed: Duration = timing.iter().sum();
+        eprintln!(
+            "Completed {} invocations in {:?} ({:?} per invocation)",
+            timing.len(),
+            elapsed,
+            elapsed.div_f64(timing.len() as f64)
+        );
+    }
+
+    let output = Output {
+        impl_kind,
+        timing: &timing,
+        v_in,
+        v_out,
+    };
+    match options.output.as_deref() {
+        Some("-") => npy::write(&mut io::stdout().lock(), v_out)?,
+        Some(path) => {
+            let mut file = fs::File::create(path)
+                .map_err(|e| format!("Failed to create output file {}: {}", path, e))?;
+            npy::write(&mut file, v_out)?
+        }
+        None => (),
+    }
+    match (options.output.as_deref(), options.format) {
+        (Some(_), None) => Ok(()),
+        (_, format) => output.write(format.unwrap_or_default()),
     }
 }
 
 fn main() -> ! {
     let verbose = env::var("VERBOSE").is_ok();
-    let args: Vec<String> = env::args().collect();
+    let (options, args) = match Options::parse(&env::args().collect::<Vec<String>>()) {
+        Ok((options, args)) => (options, args),
+        Err(m) => exit(ExitCode::Error, Some(&m)),
+    };
+    if args.get(1).map(String::as_str) == Some("bench") {
+        let impl_kind = match args.get(2) {
+            Some(arg) if args.len() == 3 => match arg.parse::<i8>() {
+                Ok(n) => Some(scan::ImplKind::from(n)),
+                Err(m) => exit(ExitCode::Error, Some(&m.to_string())),
+            },
+            Some(_) => exit(
+                ExitCode::Error,
+                Some(&format!(
+                    "Expected at most one argument after bench: {}",
+                    USAGE
+                )),
+            ),
+            None => None,
+        };
+        match bench::run(impl_kind, &options, verbose) {
+            Ok(()) => exit(ExitCode::Ok, None),
+            Err(m) => exit(ExitCode::Error, Some(&m)),
+        }
+    }
+
     let n_args = args.len() as isize;
-    if n_args < 3 {
+    let has_input = options.input.is_some() || options.random.is_some();
+    if n_args < 2 || (!has_input && n_args < 3) {
         exit(
             ExitCode::Error,
             Some(&format!(
-                "{}\n{}",
+                "{}\n{}\n{}\n{}\n{}\n{}\n{}",
                 USAGE,
-                scan::ImplKind::get_options_string()
+                scan::ImplKind::get_options_string(),
+                OperatorKind::get_options_string(),
+                ElementKind::get_options_string(),
+                AccuracyKind::get_options_string(),
+                FormatKind::get_options_string(),
+                DistKind::get_options_string(),
             )),
         );
     }
 
-    let impl_id: i8 = match args.get(1).unwrap_or(&"-1".to_string()).parse::<i8>() {
-        Ok(n) => n,
-        Err(m) => exit(ExitCode::Error, Some(&m.to_string())),
-    };
-    let impl_kind = scan::ImplKind::from(impl_id);
-
-    if verbose {
-        eprintln!("Selected implementation:\n{}", impl_kind.to_option_string());
-    }
-
-    let n: isize = match args.get(2).unwrap_or(&"-1".to_string()).parse::<isize>() {
-        Ok(n) => n,
-        Err(m) => exit(ExitCode::Error, Some(&m.to_string())),
+    let impl_kind = match args.get(1).unwrap_or(&"-1".to_string()).as_str() {
+        "check" => None,
+        arg => match arg.parse::<i8>() {
+            Ok(n) => Some(scan::ImplKind::from(n)),
+            Err(m) => exit(ExitCode::Error, Some(&m.to_string())),
+        },
     };
 
     if verbose {
-        eprintln!("Found array length N = {}", n);
+        if let Some(impl_kind) = impl_kind {
+            eprintln!("Selected implementation:\n{}", impl_kind.to_option_string());
+        }
+        eprintln!("Selected operator:\n{}", options.op.to_option_string());
+        eprintln!("Selected accuracy mode: {}", options.accuracy);
+        eprintln!("Selected scan kind: {}", options.kind);
+        eprintln!("Selected number of SIMD lanes: {}", options.get_lanes());
+        if let Some(threads) = options.threads {
+            eprintln!("Selected number of worker threads: {}", threads);
+        }
     }
 
-    match n.cmp(&0) {
-        cmp::Ordering::Less => exit(
+    let input = match (&options.input, options.random) {
+        (Some(_), Some(_)) => exit(
             ExitCode::Error,
-            Some(&format!("Expected positive array length ({})", n)),
+            Some("Expected only one of --input or --random"),
         ),
-        cmp::Ordering::Equal => exit(ExitCode::Ok, Some("Empty array (N=0)")),
-        _ => (),
-    }
+        (None, Some(n)) => {
+            if n_args != 2 {
+                exit(
+                    ExitCode::Error,
+                    Some(&format!(
+                        "Expected no arguments after Impl with --random: {}",
+                        USAGE
+                    )),
+                );
+            }
+            if verbose {
+                eprintln!(
+                    "Generating random input (seed {}, distribution {})",
+                    options.get_seed(),
+                    options.get_dist()
+                );
+            }
+            Input::Random(n, options.get_seed(), options.get_dist())
+        }
+        (Some(path), None) => {
+            if n_args != 2 {
+                exit(
+                    ExitCode::Error,
+                    Some(&format!(
+                        "Expected no arguments after Impl with --input: {}",
+                        USAGE
+                    )),
+                );
+            }
+            match Input::read(path, options.binary) {
+                Ok(input) => input,
+                Err(m) => exit(ExitCode::Error, Some(&m)),
+            }
+        }
+        (None, None) => {
+            if options.binary {
+                exit(ExitCode::Error, Some("Expected --input with --binary"));
+            }
+            let n: isize = match args.get(2).unwrap_or(&"-1".to_string()).parse::<isize>() {
+                Ok(n) => n,
+                Err(m) => exit(ExitCode::Error, Some(&m.to_string())),
+            };
+            if n < 0 {
+                exit(
+                    ExitCode::Error,
+                    Some(&format!("Expected positive array length ({})", n)),
+                );
+            }
+            if n_args != (n + 3) {
+                exit(
+                    ExitCode::Error,
+                    Some(&format!("Expected {} arguments after N: {}", n, USAGE)),
+                );
+            }
+            Input::Args(args[3..].to_vec())
+        }
+    };
 
-    if n_args != (n + 3) {
+    if options.output.as_deref() == Some("-") && options.format.is_some() {
         exit(
             ExitCode::Error,
-            Some(&format!("Expected {} arguments after N: {}", n, USAGE)),
+            Some("Expected only one of --format or --output=-"),
         );
     }
-    let v: Vec<TInt> = match parse_vec(&args[3..(3 + (n as usize))], TInt::MIN) {
-        Ok(v) => v,
-        Err(m) => exit(ExitCode::Error, Some(m)),
+
+    let element = match options.get_element(input.get_element()) {
+        Ok(element) => element,
+        Err(m) => exit(ExitCode::Error, Some(&m)),
     };
 
     if verbose {
-        eprintln!("Found input vector: {:?}", v);
+        eprintln!("Selected element type: {}", element);
     }
 
-    let (v_in, _backing_store_in) = support::alloc_aligned::<TInt, u64>(n as usize, 0);
-    let (v_out, _backing_store_out) = support::alloc_aligned::<TInt, u64>(n as usize, 0);
-
-    if let Err(m) = support::copy(&v, v_in) {
-        exit(ExitCode::Error, Some(&m));
-    };
-
-    let result = if impl_kind.is_parallel() {
-        impl_kind.dispatch_parallel::<TInt, 4>(0, v_in, v_out, verbose)
-    } else if impl_kind.is_simd() {
-        impl_kind.dispatch_simd::<TInt, 4>(0, v_in, v_out, verbose)
-    } else {
-        impl_kind.dispatch::<TInt>(0, v_in, v_out, verbose)
+    let result = match element {
+        ElementKind::I8 => run::<i8>(impl_kind, &options, &input, verbose),
+        ElementKind::I16 => run::<i16>(impl_kind, &options, &input, verbose),
+        ElementKind::I32 => run::<i32>(impl_kind, &options, &input, verbose),
+        ElementKind::I64 => run::<i64>(impl_kind, &options, &input, verbose),
+        ElementKind::Isize => run::<isize>(impl_kind, &options, &input, verbose),
+        ElementKind::U8 => run::<u8>(impl_kind, &options, &input, verbose),
+        ElementKind::U16 => run::<u16>(impl_kind, &options, &input, verbose),
+        ElementKind::U32 => run::<u32>(impl_kind, &options, &input, verbose),
+        ElementKind::U64 => run::<u64>(impl_kind, &options, &input, verbose),
+        ElementKind::Usize => run::<usize>(impl_kind, &options, &input, verbose),
+        ElementKind::F32 => run::<f32>(impl_kind, &options, &input, verbose),
+        ElementKind::F64 => run::<f64>(impl_kind, &options, &input, verbose),
     };
 
     match result {
@@ -110,8 +312,5 @@ fn main() -> ! {
         Err(m) => exit(ExitCode::Error, Some(&m)),
     };
 
-    println!("in  : {:?}", v_in);
-    println!("out : {:?}", v_out);
-
     exit(ExitCode::Ok, None);
 }
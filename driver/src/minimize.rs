@@ -0,0 +1,395 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use support::OperatorKind;
+use support::ScanKind;
+
+use crate::check;
+use crate::element::AccuracyKind;
+use crate::element::IElement;
+use crate::options::Options;
+use crate::scan::ImplKind;
+
+/// An element type which can be shrunk towards simpler values (i.e., zero, then integers of
+/// smaller magnitude, then positive values) while minimising an input vector.
+pub trait IShrink: Sized {
+    /// Returns the values strictly simpler than `self`, in order of preference.
+    fn get_shrink_candidates(self) -> Vec<Self>;
+}
+
+/// Returns the candidates without duplicates and without `x`.
+fn get_unique<T: Copy + PartialEq>(x: T, v: Vec<T>) -> Vec<T> {
+    let mut v_unique: Vec<T> = Vec::with_capacity(v.len());
+    for y in v {
+        if y != x && !v_unique.contains(&y) {
+            v_unique.push(y);
+        }
+    }
+    v_unique
+}
+
+macro_rules! ImplShrinkInt {
+    () => {};
+    ($T:ty) => {
+        impl IShrink for $T {
+            fn get_shrink_candidates(self) -> Vec<Self> {
+                let mut v: Vec<Self> = Vec::new();
+                if self == 0 {
+                    return v;
+                }
+                v.push(0);
+                v.push(self / 2);
+                if self > 0 {
+                    v.push(self - 1);
+                }
+                if let Some(x) = self.checked_neg().filter(|&x| x > self) {
+                    v.push(x);
+                    v.push(self + 1);
+                }
+                get_unique(self, v)
+            }
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplShrinkInt!($T);
+        ImplShrinkInt!($($tail)*);
+    };
+}
+ImplShrinkInt!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! ImplShrinkFloat {
+    () => {};
+    ($T:ty) => {
+        impl IShrink for $T {
+            fn get_shrink_candidates(self) -> Vec<Self> {
+                let mut v: Vec<Self> = Vec::new();
+                if self == 0.0 {
+                    return v;
+                }
+                v.push(0.0);
+                if self.is_nan() {
+                    return v;
+                }
+                if self.is_infinite() {
+                    v.push(self.signum());
+                } else if self.trunc() != self {
+                    v.push(self.trunc());
+                } else {
+                    v.push((self / 2.0).trunc());
+                    if self.abs() > 1.0 {
+                        v.push(self - self.signum());
+                    }
+                }
+                if self < 0.0 {
+                    v.push(-self);
+                }
+                get_unique(self, v)
+            }
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplShrinkFloat!($T);
+        ImplShrinkFloat!($($tail)*);
+    };
+}
+ImplShrinkFloat!(f32, f64);
+
+/// An input vector (with its segment head flags, if any) on which an implementation disagrees
+/// with the reference implementation.
+struct Reproducer<T> {
+    v_in: Vec<T>,
+    flags: Option<Vec<bool>>,
+}
+
+impl<T> Reproducer<T>
+where
+    T: IElement,
+{
+    /// Returns the output of the reference implementation, if the implementation disagrees with
+    /// it on this input.
+    fn get_mismatch(
+        &self,
+        impl_kind: ImplKind,
+        impl_reference: ImplKind,
+        options: &Options,
+    ) -> Option<Vec<T>> {
+        let n = self.v_in.len();
+        let flags = self.flags.as_deref();
+        let (v_in, _backing_store_in) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+        let (v_ref, _backing_store_ref) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+        let (v_out, _backing_store_out) = support::alloc_aligned::<T, u64>(n, T::zeroed());
+        v_in.copy_from_slice(&self.v_in);
+        T::dispatch(impl_reference, options, flags, v_in, v_ref, false).ok()?;
+        T::dispatch(impl_kind, options, flags, v_in, v_out, false).ok()?;
+        check::get_first_mismatch(v_ref, v_out).map(|_| v_ref.to_vec())
+    }
+
+    /// Returns the input without the `k` elements (and flags) starting at index `i`.
+    fn without(&self, i: usize, k: usize) -> Self {
+        Self {
+            v_in: [&self.v_in[..i], &self.v_in[i + k..]].concat(),
+            flags: self
+                .flags
+                .as_ref()
+                .map(|flags| [&flags[..i], &flags[i + k..]].concat()),
+        }
+    }
+
+    /// Returns the input with the element at index `i` replaced by `x`.
+    fn with(&self, i: usize, x: T) -> Self {
+        let mut v_in = self.v_in.clone();
+        v_in[i] = x;
+        Self {
+            v_in,
+            flags: self.flags.clone(),
+        }
+    }
+}
+
+/// Shrink the input vector on which `impl_kind` disagrees with `impl_reference` (first at index
+/// `i_mismatch`), while the mismatch persists: first by removing (halving) chunks of elements,
+/// then by replacing elements with simpler values (see `IShrink`). Returns the test-scan golden
+/// vector file (or, for segmented scans, the constants and test function) reproducing the minimal
+/// input.
+pub fn run<T>(
+    impl_kind: ImplKind,
+    impl_reference: ImplKind,
+    options: &Options,
+    flags: Option<&[bool]>,
+    v_in: &[T],
+    i_mismatch: usize,
+) -> Result<String, String>
+where
+    T: IElement,
+{
+    if options.accuracy != AccuracyKind::Plain {
+        return Err(format!(
+            "Expected accuracy mode {} (found {})",
+            AccuracyKind::Plain,
+            options.accuracy
+        ));
+    }
+    let mut repro = Reproducer {
+        v_in: v_in.to_vec(),
+        flags: flags.map(<[bool]>::to_vec),
+    };
+    let prefix = repro.without(i_mismatch + 1, v_in.len() - i_mismatch - 1);
+    if prefix
+        .get_mismatch(impl_kind, impl_reference, options)
+        .is_some()
+    {
+        repro = prefix;
+    }
+    loop {
+        let is_shrunk_length = shrink_length(&mut repro, impl_kind, impl_reference, options);
+        let is_shrunk_values = shrink_values(&mut repro, impl_kind, impl_reference, options);
+        if !is_shrunk_length && !is_shrunk_values {
+            break;
+        }
+    }
+    let v_ref = repro
+        .get_mismatch(impl_kind, impl_reference, options)
+        .ok_or(format!("Failed to reproduce the mismatch of {}", impl_kind))?;
+    // NOTE: Golden vector files have no segment head flags, so segmented scans are reproduced by
+    // test functions instead
+    Ok(match repro.flags.as_deref() {
+        Some(flags) => to_test(impl_kind, options, &repro, flags, &v_ref),
+        None => to_vector(impl_kind, impl_reference, options, &repro, &v_ref),
+    })
+}
+
+fn shrink_length<T: IElement>(
+    repro: &mut Reproducer<T>,
+    impl_kind: ImplKind,
+    impl_reference: ImplKind,
+    options: &Options,
+) -> bool {
+    let mut is_shrunk = false;
+    let mut k = repro.v_in.len() / 2;
+    while k > 0 {
+        let mut i = 0;
+        while i + k <= repro.v_in.len() && repro.v_in.len() > k {
+            let candidate = repro.without(i, k);
+            if candidate
+                .get_mismatch(impl_kind, impl_reference, options)
+                .is_some()
+            {
+                *repro = candidate;
+                is_shrunk = true;
+            } else {
+                i += k;
+            }
+        }
+        k /= 2;
+    }
+    is_shrunk
+}
+
+fn shrink_values<T: IElement>(
+    repro: &mut Reproducer<T>,
+    impl_kind: ImplKind,
+    impl_reference: ImplKind,
+    options: &Options,
+) -> bool {
+    let mut is_shrunk = false;
+    for i in 0..repro.v_in.len() {
+        'shrink: loop {
+            for x in repro.v_in[i].get_shrink_candidates() {
+                let candidate = repro.with(i, x);
+                if candidate
+                    .get_mismatch(impl_kind, impl_reference, options)
+                    .is_some()
+                {
+                    *repro = candidate;
+                    is_shrunk = true;
+                    continue 'shrink;
+                }
+            }
+            break;
+        }
+    }
+    is_shrunk
+}
+
+/// Returns the prefix of the names of the test functions of the implementation in test-scan.
+fn get_test_prefix(impl_kind: ImplKind) -> &'static str {
+    match impl_kind {
+        ImplKind::Sequential => "seq",
+        ImplKind::SequentialNaive => "seq_naive",
+        ImplKind::SequentialNaiveDoubleBuffer => "seq_naive_2x",
+        ImplKind::SimdNaiveDoubleBuffer => "simd_naive_2x",
+        ImplKind::ParallelCPUNaiveDoubleBuffer => "par_cpu_naive_2x",
+        ImplKind::ParallelGPUNaiveDoubleBuffer => "par_gpu_naive_2x",
+        ImplKind::SequentialBlelloch => "seq_blelloch",
+        ImplKind::ParallelCPUBlelloch => "par_cpu_blelloch",
+        ImplKind::ParallelCPULookback => "par_cpu_lookback",
+        ImplKind::ParallelCPUReduceScan => "par_cpu_reduce_scan",
+        ImplKind::ParallelGPUBlelloch => "par_gpu_blelloch",
+    }
+}
+
+/// Returns the call of the `scan_segmented` helper of the test-scan tests of the implementation
+/// reproducing the segmented scan, with the element type inferred from the constants.
+fn to_test_scan(impl_kind: ImplKind, options: &Options, flags: &[bool]) -> String {
+    let op = match options.op {
+        OperatorKind::Add => "OpAdd",
+        OperatorKind::Mul => "OpMul",
+        OperatorKind::Min => "OpMin",
+        OperatorKind::Max => "OpMax",
+        OperatorKind::BAnd => "OpBAnd",
+        OperatorKind::BOr => "OpBOr",
+        OperatorKind::BXor => "OpBXor",
+    };
+    let params = if impl_kind.is_simd() || impl_kind.is_parallel() {
+        format!("_, {}, {}", op, options.get_lanes())
+    } else {
+        format!("_, {}", op)
+    };
+    let kind = match options.kind {
+        ScanKind::Exclusive => "ScanKind::Exclusive",
+        ScanKind::Inclusive => "ScanKind::Inclusive",
+    };
+    let flags: Vec<String> = flags.iter().map(bool::to_string).collect();
+    format!(
+        "scan_segmented::<{}>({}, &[{}])",
+        params,
+        kind,
+        flags.join(", ")
+    )
+}
+
+/// Returns the element as a Rust literal (or constant) of its type.
+fn to_literal<T: IElement>(x: T) -> String {
+    let s = format!("{:?}", x);
+    match s.as_str() {
+        "inf" => format!("{}::INFINITY", T::KIND),
+        "-inf" => format!("{}::NEG_INFINITY", T::KIND),
+        "NaN" => format!("{}::NAN", T::KIND),
+        _ => s,
+    }
+}
+
+/// Returns the path and contents of the test-scan golden vector file of the input and reference
+/// output (see `test_scan::golden`), where the elements are written as parsed by `FromStr`
+/// (e.g., `inf` or `NaN`).
+fn to_vector<T: IElement>(
+    impl_kind: ImplKind,
+    impl_reference: ImplKind,
+    options: &Options,
+    repro: &Reproducer<T>,
+    v_ref: &[T],
+) -> String {
+    let n = repro.v_in.len();
+    let name = format!("n{}_repro_{}", n, get_test_prefix(impl_kind));
+    let to_elements = |v: &[T]| {
+        let v: Vec<String> = v.iter().map(|x| format!("{:?}", x)).collect();
+        v.join(" ")
+    };
+    format!(
+        "test-scan/vectors/{}.txt:\n\n\
+        # {} {} scan of {} ({} elements of type {})\n\
+        # NOTE: Minimised from a mismatch of {} with {} (lanes = {}, threads = {})\n\
+        op = {}\n\
+        type = {}\n\
+        kind = {}\n\
+        in = {}\n\
+        out = {}",
+        name,
+        options.kind,
+        options.op,
+        name.to_uppercase(),
+        n,
+        T::KIND,
+        impl_kind,
+        impl_reference,
+        options.get_lanes(),
+        options
+            .threads
+            .map_or("default".to_string(), |threads| threads.to_string()),
+        options.op,
+        T::KIND,
+        options.kind.to_string().to_lowercase(),
+        to_elements(&repro.v_in),
+        to_elements(v_ref)
+    )
+}
+
+/// Returns the test-scan constants of the input, segment head flags and reference output, and
+/// the (in-process) test function of the implementation (unformatted, e.g., for long vectors,
+/// until formatted by rustfmt).
+fn to_test<T: IElement>(
+    impl_kind: ImplKind,
+    options: &Options,
+    repro: &Reproducer<T>,
+    flags: &[bool],
+    v_ref: &[T],
+) -> String {
+    let n = repro.v_in.len();
+    let name = format!("N{}_REPRO", n);
+    let to_const = |suffix: &str, v: &[T]| {
+        let v: Vec<String> = v.iter().map(|&x| to_literal(x)).collect();
+        format!(
+            "pub const {}_{}: [{}; {}] = [{}];",
+            name,
+            suffix,
+            T::KIND,
+            n,
+            v.join(", ")
+        )
+    };
+    let body = format!(
+        "test_body(\"{}\", {}, &{}_IN, &{}_OUT);",
+        impl_kind,
+        to_test_scan(impl_kind, options, flags),
+        name,
+        name
+    );
+    format!(
+        "{}\n{}\n\n    #[test]\n    fn test_{}_repro_n{}() {{\n        {}\n    }}",
+        to_const("IN", &repro.v_in),
+        to_const("OUT", v_ref),
+        get_test_prefix(impl_kind),
+        n,
+        body
+    )
+}
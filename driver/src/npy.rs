@@ -0,0 +1,171 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::io::Write;
+use std::mem;
+
+use crate::element::ElementKind;
+use crate::element::IElement;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// The total length of the preamble and header is padded to a multiple of this alignment.
+const ALIGN: usize = 64;
+
+/// Returns true if `bytes` start with the magic string of the NumPy `.npy` format (see
+/// `https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html`).
+pub fn is_npy(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Split the contents of a `.npy` file (of format version 1.0, 2.0 or 3.0) into the element type
+/// of its 1-D array and the (little-endian) bytes of its elements.
+pub fn read(mut bytes: Vec<u8>) -> Result<(ElementKind, Vec<u8>), String> {
+    if !is_npy(&bytes) || bytes.len() < MAGIC.len() + 4 {
+        return Err("Expected .npy magic string".to_string());
+    }
+    let major = bytes[MAGIC.len()];
+    let n_preamble = MAGIC.len() + 2;
+    let (n_len, header_len) = match major {
+        1 => (
+            2,
+            u16::from_le_bytes([bytes[n_preamble], bytes[n_preamble + 1]]) as usize,
+        ),
+        2 | 3 if bytes.len() >= n_preamble + 4 => {
+            let mut a = [0u8; 4];
+            a.copy_from_slice(&bytes[n_preamble..(n_preamble + 4)]);
+            (4, u32::from_le_bytes(a) as usize)
+        }
+        _ => return Err(format!("Unsupported .npy format version: {}", major)),
+    };
+    let offset = n_preamble + n_len + header_len;
+    if bytes.len() < offset {
+        return Err("Expected .npy header within the file".to_string());
+    }
+    let header = String::from_utf8_lossy(&bytes[(n_preamble + n_len)..offset]).into_owned();
+    let kind = from_descr(get_header_str(&header, "descr")?)?;
+    let n = match get_shape(&header)?.as_slice() {
+        [n] => *n,
+        shape => return Err(format!("Expected 1-D .npy array (found shape {:?})", shape)),
+    };
+    let n_bytes = n.saturating_mul(get_size(kind));
+    if bytes.len() - offset < n_bytes {
+        return Err(format!(
+            "Expected {} elements of .npy array (found {} bytes)",
+            n,
+            bytes.len() - offset
+        ));
+    }
+    bytes.drain(..offset);
+    bytes.truncate(n_bytes);
+    Ok((kind, bytes))
+}
+
+/// Write `v` as a 1-D `.npy` array (version 1.0) of its element type.
+pub fn write<T, W>(w: &mut W, v: &[T]) -> Result<(), String>
+where
+    T: IElement,
+    W: Write,
+{
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({},), }}",
+        to_descr(T::KIND),
+        v.len()
+    );
+    let n_preamble = MAGIC.len() + 4;
+    let n_pad = ALIGN - (n_preamble + header.len() + 1) % ALIGN;
+    header.extend(std::iter::repeat_n(' ', n_pad % ALIGN));
+    header.push('\n');
+    let mut bytes: Vec<u8> = Vec::with_capacity(n_preamble + header.len() + mem::size_of_val(v));
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    let n_header = bytes.len();
+    bytes.resize(n_header + mem::size_of_val(v), 0);
+    bytes[n_header..]
+        .chunks_exact_mut(mem::size_of::<T>())
+        .zip(v.iter())
+        .for_each(|(b, x)| x.write_le_bytes(b));
+    w.write_all(&bytes)
+        .map_err(|e| format!("Failed to write .npy array: {}", e))
+}
+
+fn get_size(kind: ElementKind) -> usize {
+    match kind {
+        ElementKind::I8 | ElementKind::U8 => 1,
+        ElementKind::I16 | ElementKind::U16 => 2,
+        ElementKind::I32 | ElementKind::U32 | ElementKind::F32 => 4,
+        ElementKind::I64 | ElementKind::U64 | ElementKind::F64 => 8,
+        ElementKind::Isize => mem::size_of::<isize>(),
+        ElementKind::Usize => mem::size_of::<usize>(),
+    }
+}
+
+fn to_descr(kind: ElementKind) -> String {
+    let c = match kind {
+        ElementKind::I8 | ElementKind::I16 | ElementKind::I32 => 'i',
+        ElementKind::I64 | ElementKind::Isize => 'i',
+        ElementKind::U8 | ElementKind::U16 | ElementKind::U32 => 'u',
+        ElementKind::U64 | ElementKind::Usize => 'u',
+        ElementKind::F32 | ElementKind::F64 => 'f',
+    };
+    let order = if get_size(kind) == 1 { '|' } else { '<' };
+    format!("{}{}{}", order, c, get_size(kind))
+}
+
+// NOTE: Only little-endian dtypes are supported, and `isize` (resp. `usize`) arrays are read as
+// `i64` (resp. `u64`) on 64-bit platforms.
+fn from_descr(descr: &str) -> Result<ElementKind, String> {
+    let (order, dtype) = descr.split_at(descr.len().min(1));
+    match (order, dtype) {
+        ("<" | "|", "i1") => Ok(ElementKind::I8),
+        ("<" | "|", "u1") => Ok(ElementKind::U8),
+        ("<", "i2") => Ok(ElementKind::I16),
+        ("<", "i4") => Ok(ElementKind::I32),
+        ("<", "i8") => Ok(ElementKind::I64),
+        ("<", "u2") => Ok(ElementKind::U16),
+        ("<", "u4") => Ok(ElementKind::U32),
+        ("<", "u8") => Ok(ElementKind::U64),
+        ("<", "f4") => Ok(ElementKind::F32),
+        ("<", "f8") => Ok(ElementKind::F64),
+        _ => Err(format!("Unsupported .npy dtype: {}", descr)),
+    }
+}
+
+/// Returns the (unparsed) value of `key` in the Python dictionary literal of the `.npy` header.
+fn get_header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, String> {
+    let pattern = format!("'{}':", key);
+    let err = || format!("Expected key '{}' in .npy header", key);
+    let start = header.find(&pattern).ok_or_else(err)? + pattern.len();
+    let value = header[start..].trim_start();
+    let end = if value.starts_with('(') {
+        value.find(')').map(|i| i + 1)
+    } else {
+        value.find([',', '}'])
+    };
+    Ok(value[..end.ok_or_else(err)?].trim_end())
+}
+
+fn get_header_str<'a>(header: &'a str, key: &str) -> Result<&'a str, String> {
+    let value = get_header_value(header, key)?;
+    value
+        .strip_prefix('\'')
+        .and_then(|s| s.strip_suffix('\''))
+        .ok_or(format!("Expected string value of '{}' in .npy header", key))
+}
+
+fn get_shape(header: &str) -> Result<Vec<usize>, String> {
+    let value = get_header_value(header, "shape")?;
+    value
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            s.parse::<usize>()
+                .or(Err(format!("Failed to parse .npy shape: {}", value)))
+        })
+        .collect()
+}
@@ -0,0 +1,217 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use support::flags_from_offsets;
+use support::OperatorKind;
+use support::ScanKind;
+
+use crate::element::AccuracyKind;
+use crate::element::ElementKind;
+use crate::element::LANES;
+use crate::output::FormatKind;
+use crate::random::DistKind;
+
+#[derive(Clone, Default)]
+pub struct Options {
+    pub accuracy: AccuracyKind,
+    pub binary: bool,
+    pub dist: Option<DistKind>,
+    pub element: Option<ElementKind>,
+    pub flags: Option<Vec<bool>>,
+    pub format: Option<FormatKind>,
+    pub input: Option<String>,
+    pub kind: ScanKind,
+    pub lanes: Option<usize>,
+    pub offsets: Option<Vec<usize>>,
+    pub op: OperatorKind,
+    pub output: Option<String>,
+    pub random: Option<usize>,
+    pub repeat: Option<usize>,
+    pub seed: Option<u64>,
+    pub sizes: Option<Vec<usize>>,
+    pub threads: Option<usize>,
+    pub warmup: Option<usize>,
+}
+
+impl Options {
+    /// Split the command line arguments into options (prefixed by `--`) and positional arguments.
+    /// The program name is kept as the first positional argument.
+    pub fn parse(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let mut options = Self::default();
+        let mut positional: Vec<String> = Vec::new();
+        for arg in args {
+            match arg.strip_prefix("--") {
+                Some(opt) => options.parse_option(opt)?,
+                None => positional.push(arg.clone()),
+            }
+        }
+        Ok((options, positional))
+    }
+
+    fn parse_option(&mut self, opt: &str) -> Result<(), String> {
+        let (key, value) = match opt.split_once('=') {
+            Some((key, value)) => (key, Some(value)),
+            None => (opt, None),
+        };
+        match (key, value) {
+            ("accuracy", Some(value)) => self.accuracy = value.parse::<AccuracyKind>()?,
+            ("binary", None) => self.binary = true,
+            ("dist", Some(value)) => self.dist = Some(value.parse::<DistKind>()?),
+            ("flags", Some(value)) => self.flags = Some(Self::parse_flags(value)?),
+            ("format", Some(value)) => self.format = Some(value.parse::<FormatKind>()?),
+            ("inclusive", None) => self.kind = ScanKind::Inclusive,
+            ("input", Some(value)) => self.input = Some(value.to_string()),
+            ("lanes", Some(value)) => self.lanes = Some(Self::parse_lanes(value)?),
+            ("offsets", Some(value)) => self.offsets = Some(Self::parse_offsets(value)?),
+            ("op", Some(value)) => self.op = value.parse::<OperatorKind>()?,
+            ("output", Some(value)) => self.output = Some(value.to_string()),
+            ("random", Some(value)) => self.random = Some(Self::parse_random(value)?),
+            ("repeat", Some(value)) => self.repeat = Some(Self::parse_repeat(value)?),
+            ("seed", Some(value)) => self.seed = Some(Self::parse_seed(value)?),
+            ("sizes", Some(value)) => self.sizes = Some(Self::parse_sizes(value)?),
+            ("threads", Some(value)) => self.threads = Some(Self::parse_threads(value)?),
+            ("type", Some(value)) => self.element = Some(value.parse::<ElementKind>()?),
+            ("warmup", Some(value)) => self.warmup = Some(Self::parse_warmup(value)?),
+            _ => return Err(format!("Invalid option: --{}", opt)),
+        }
+        Ok(())
+    }
+
+    fn parse_flags(value: &str) -> Result<Vec<bool>, String> {
+        value
+            .split(',')
+            .map(|s| match s {
+                "0" => Ok(false),
+                "1" => Ok(true),
+                _ => Err(format!("Failed to parse segment head flag: {}", s)),
+            })
+            .collect()
+    }
+
+    fn parse_lanes(value: &str) -> Result<usize, String> {
+        match value.parse::<usize>() {
+            Ok(n) if LANES.contains(&n) => Ok(n),
+            Ok(n) => Err(format!(
+                "Unsupported number of SIMD lanes: {} (expected one of {:?})",
+                n, LANES
+            )),
+            Err(_) => Err(format!("Failed to parse number of SIMD lanes: {}", value)),
+        }
+    }
+
+    fn parse_offsets(value: &str) -> Result<Vec<usize>, String> {
+        value
+            .split(',')
+            .map(|s| {
+                s.parse::<usize>()
+                    .or(Err(format!("Failed to parse segment offset: {}", s)))
+            })
+            .collect()
+    }
+
+    fn parse_random(value: &str) -> Result<usize, String> {
+        value.parse::<usize>().or(Err(format!(
+            "Failed to parse length of random input: {}",
+            value
+        )))
+    }
+
+    fn parse_repeat(value: &str) -> Result<usize, String> {
+        match value.parse::<usize>() {
+            Ok(0) => Err("Expected non-zero number of repetitions".to_string()),
+            Ok(n) => Ok(n),
+            Err(_) => Err(format!("Failed to parse number of repetitions: {}", value)),
+        }
+    }
+
+    fn parse_seed(value: &str) -> Result<u64, String> {
+        value
+            .parse::<u64>()
+            .or(Err(format!("Failed to parse random seed: {}", value)))
+    }
+
+    fn parse_sizes(value: &str) -> Result<Vec<usize>, String> {
+        value
+            .split(',')
+            .map(|s| match s.parse::<usize>() {
+                Ok(0) => Err("Expected non-zero input size".to_string()),
+                Ok(n) => Ok(n),
+                Err(_) => Err(format!("Failed to parse input size: {}", s)),
+            })
+            .collect()
+    }
+
+    fn parse_threads(value: &str) -> Result<usize, String> {
+        value.parse::<usize>().or(Err(format!(
+            "Failed to parse number of worker threads: {}",
+            value
+        )))
+    }
+
+    fn parse_warmup(value: &str) -> Result<usize, String> {
+        match value.parse::<usize>() {
+            Ok(0) => Err("Expected non-zero number of warm-up invocations".to_string()),
+            Ok(n) => Ok(n),
+            Err(_) => Err(format!(
+                "Failed to parse number of warm-up invocations: {}",
+                value
+            )),
+        }
+    }
+
+    /// Returns the element type, which must match the element type of the input (if fixed by the
+    /// input format, e.g., `.npy`).
+    pub fn get_element(&self, input: Option<ElementKind>) -> Result<ElementKind, String> {
+        match (self.element, input) {
+            (Some(element), Some(kind)) if element != kind => Err(format!(
+                "Expected element type {} of input (found --type={})",
+                kind, element
+            )),
+            (_, Some(kind)) => Ok(kind),
+            (element, None) => Ok(element.unwrap_or_default()),
+        }
+    }
+
+    /// Returns the number of untimed invocations preceding the timed invocations of a benchmark.
+    /// At least one is required, so that lazy setup costs (e.g., of the GPU context) are excluded.
+    pub fn get_warmup(&self) -> usize {
+        self.warmup.unwrap_or(3)
+    }
+
+    pub fn get_lanes(&self) -> usize {
+        self.lanes.unwrap_or(4)
+    }
+
+    pub fn get_repeat(&self) -> usize {
+        self.repeat.unwrap_or(1)
+    }
+
+    /// Returns the number of timed invocations of a benchmark.
+    pub fn get_repeat_bench(&self) -> usize {
+        self.repeat.unwrap_or(10)
+    }
+
+    pub fn get_dist(&self) -> DistKind {
+        self.dist.unwrap_or_default()
+    }
+
+    pub fn get_seed(&self) -> u64 {
+        self.seed.unwrap_or(0)
+    }
+
+    pub fn get_sizes(&self) -> Vec<usize> {
+        self.sizes
+            .clone()
+            .unwrap_or(vec![1 << 10, 1 << 16, 1 << 20])
+    }
+
+    /// Returns the segment head flags for an input of length `n`, if segments were specified.
+    pub fn get_flags(&self, n: usize) -> Result<Option<Vec<bool>>, String> {
+        match (&self.flags, &self.offsets) {
+            (Some(_), Some(_)) => Err("Expected only one of --flags or --offsets".to_string()),
+            (Some(flags), None) => Ok(Some(flags.clone())),
+            (None, Some(offsets)) => Ok(Some(flags_from_offsets(n, offsets)?)),
+            (None, None) => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::element::IElement;
+use crate::scan::ImplKind;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub enum FormatKind {
+    #[default]
+    Text,
+    Json,
+    Csv,
+    Binary,
+}
+
+/// The result of a scan, written to stdout in the selected format.
+pub struct Output<'a, T> {
+    pub impl_kind: ImplKind,
+    pub timing: &'a [Duration],
+    pub v_in: &'a [T],
+    pub v_out: &'a [T],
+}
+
+impl<T> Output<'_, T>
+where
+    T: IElement,
+{
+    pub fn write(&self, format: FormatKind) -> Result<(), String> {
+        let mut stdout = io::stdout().lock();
+        match format {
+            FormatKind::Text => self.write_text(&mut stdout),
+            FormatKind::Json => self.write_json(&mut stdout),
+            FormatKind::Csv => self.write_csv(&mut stdout),
+            FormatKind::Binary => self.write_binary(&mut stdout),
+        }
+        .map_err(|e| format!("Failed to write output: {}", e))
+    }
+
+    fn write_text(&self, w: &mut impl Write) -> io::Result<()> {
+        writeln!(w, "in  : {:?}", self.v_in)?;
+        writeln!(w, "out : {:?}", self.v_out)
+    }
+
+    /// Non-finite floating point elements are written as `Infinity`, `-Infinity` and `NaN`
+    /// (as by the `json` module of Python).
+    fn write_json(&self, w: &mut impl Write) -> io::Result<()> {
+        let time_ns: u128 = self.timing.iter().map(Duration::as_nanos).sum();
+        let output: Vec<String> = self.v_out.iter().map(|x| to_json(*x)).collect();
+        writeln!(
+            w,
+            "{{\"impl\": \"{}\", \"type\": \"{}\", \"n\": {}, \"repeat\": {}, \"time_ns\": {}, \
+            \"output\": [{}]}}",
+            self.impl_kind,
+            T::KIND,
+            self.v_out.len(),
+            self.timing.len(),
+            time_ns,
+            output.join(", ")
+        )
+    }
+
+    fn write_csv(&self, w: &mut impl Write) -> io::Result<()> {
+        writeln!(w, "index,in,out")?;
+        for (i, (x, y)) in self.v_in.iter().zip(self.v_out.iter()).enumerate() {
+            writeln!(w, "{},{},{}", i, x, y)?;
+        }
+        Ok(())
+    }
+
+    /// Write the output elements as raw little-endian bytes (as read by `--binary`).
+    fn write_binary(&self, w: &mut impl Write) -> io::Result<()> {
+        let mut bytes: Vec<u8> = vec![0; size_of_val(self.v_out)];
+        bytes
+            .chunks_exact_mut(size_of::<T>())
+            .zip(self.v_out.iter())
+            .for_each(|(b, x)| x.write_le_bytes(b));
+        w.write_all(&bytes)
+    }
+}
+
+fn to_json<T: IElement>(x: T) -> String {
+    let s = x.to_string();
+    match s.as_str() {
+        "inf" => "Infinity".to_string(),
+        "-inf" => "-Infinity".to_string(),
+        _ => s,
+    }
+}
+
+impl FormatKind {
+    pub fn get_options_string() -> String {
+        format!(
+            "Output formats:\n{}\n{}\n{}\n{}",
+            FormatKind::Text.to_option_string(),
+            FormatKind::Json.to_option_string(),
+            FormatKind::Csv.to_option_string(),
+            FormatKind::Binary.to_option_string(),
+        )
+    }
+
+    pub fn to_option_string(self) -> String {
+        format!("*  {}", self)
+    }
+}
+
+impl fmt::Display for FormatKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FormatKind::Text => "text",
+                FormatKind::Json => "json",
+                FormatKind::Csv => "csv",
+                FormatKind::Binary => "binary",
+            }
+        )
+    }
+}
+
+impl FromStr for FormatKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(FormatKind::Text),
+            "json" => Ok(FormatKind::Json),
+            "csv" => Ok(FormatKind::Csv),
+            "binary" => Ok(FormatKind::Binary),
+            _ => Err(format!("Invalid output format: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::fmt;
+use std::str::FromStr;
+
+use support::Rng;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub enum DistKind {
+    #[default]
+    Uniform,
+    Small,
+    Zipf,
+    AlternatingSign,
+    MaxMagnitude,
+}
+
+/// The max value of the `zipf` distribution (if representable by the element type).
+const ZIPF_MAX: f64 = 65536.0;
+
+/// An element type with random values drawn from the distributions of `DistKind`:
+/// *  `uniform`: the full range of integer types, or `[-1, 1)` for floating point types.
+/// *  `small`: the integers in `[-8, 8)` (resp. `[0, 16)` for unsigned types).
+/// *  `zipf`: the integers in `[1, 65536]` (or up to the max value), where `k` is drawn with
+///    probability roughly proportional to `1/k`.
+/// *  `alternating-sign`: values of random magnitude (up to `2^(bits/2)` for integer types, or
+///    `10^6` for floating point types) with alternating signs, where negation wraps around for
+///    unsigned types.
+/// *  `max-magnitude`: the max (or min, for signed types) values, which overflow in any sum.
+pub trait IRandom: Sized {
+    /// Returns the `k`-th element of a random input drawn from `dist`.
+    fn gen_random(rng: &mut Rng, dist: DistKind, k: usize) -> Self;
+}
+
+/// Fill `v` with random elements drawn from `dist`, reproducible from `seed`.
+pub fn fill_random<T: IRandom>(seed: u64, dist: DistKind, v: &mut [T]) {
+    let mut rng = Rng::new(seed);
+    v.iter_mut()
+        .enumerate()
+        .for_each(|(k, x)| *x = T::gen_random(&mut rng, dist, k));
+}
+
+/// Returns an integer in `[1, n_max]`, drawn with probability `ln((k + 1)/k) / ln(n_max + 1)`.
+fn gen_zipf(rng: &mut Rng, n_max: f64) -> u64 {
+    ((n_max + 1.0).powf(rng.next_f64()).floor() as u64).clamp(1, n_max as u64)
+}
+
+macro_rules! ImplRandomInt {
+    () => {};
+    ($T:ty) => {
+        impl IRandom for $T {
+            fn gen_random(rng: &mut Rng, dist: DistKind, k: usize) -> Self {
+                let is_signed = <$T>::MIN != 0;
+                match dist {
+                    DistKind::Uniform => rng.next_u64() as $T,
+                    DistKind::Small if is_signed => (rng.next_below(16) as i8 - 8) as $T,
+                    DistKind::Small => rng.next_below(16) as $T,
+                    DistKind::Zipf => gen_zipf(rng, ZIPF_MAX.min(<$T>::MAX as f64)) as $T,
+                    DistKind::AlternatingSign => {
+                        let m = rng.next_below(1 << (<$T>::BITS / 2)) as $T;
+                        if k % 2 == 0 {
+                            m
+                        } else {
+                            m.wrapping_neg()
+                        }
+                    }
+                    DistKind::MaxMagnitude if is_signed && rng.next_below(2) == 1 => <$T>::MIN,
+                    DistKind::MaxMagnitude => <$T>::MAX,
+                }
+            }
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplRandomInt!($T);
+        ImplRandomInt!($($tail)*);
+    };
+}
+ImplRandomInt!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! ImplRandomFloat {
+    () => {};
+    ($T:ty) => {
+        impl IRandom for $T {
+            fn gen_random(rng: &mut Rng, dist: DistKind, k: usize) -> Self {
+                match dist {
+                    DistKind::Uniform => (2.0 * rng.next_f64() - 1.0) as $T,
+                    DistKind::Small => (rng.next_below(16) as i8 - 8) as $T,
+                    DistKind::Zipf => gen_zipf(rng, ZIPF_MAX) as $T,
+                    DistKind::AlternatingSign => {
+                        let m = (1.0e6 * rng.next_f64()) as $T;
+                        if k % 2 == 0 {
+                            m
+                        } else {
+                            -m
+                        }
+                    }
+                    DistKind::MaxMagnitude if rng.next_below(2) == 1 => <$T>::MIN,
+                    DistKind::MaxMagnitude => <$T>::MAX,
+                }
+            }
+        }
+    };
+    ($T:ty, $($tail:tt)*) => {
+        ImplRandomFloat!($T);
+        ImplRandomFloat!($($tail)*);
+    };
+}
+ImplRandomFloat!(f32, f64);
+
+impl DistKind {
+    pub fn get_options_string() -> String {
+        format!(
+            "Random input distributions:\n{}\n{}\n{}\n{}\n{}",
+            DistKind::Uniform.to_option_string(),
+            DistKind::Small.to_option_string(),
+            DistKind::Zipf.to_option_string(),
+            DistKind::AlternatingSign.to_option_string(),
+            DistKind::MaxMagnitude.to_option_string(),
+        )
+    }
+
+    pub fn to_option_string(self) -> String {
+        format!("*  {}", self)
+    }
+}
+
+impl fmt::Display for DistKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DistKind::Uniform => "uniform",
+                DistKind::Small => "small",
+                DistKind::Zipf => "zipf",
+                DistKind::AlternatingSign => "alternating-sign",
+                DistKind::MaxMagnitude => "max-magnitude",
+            }
+        )
+    }
+}
+
+impl FromStr for DistKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(DistKind::Uniform),
+            "small" => Ok(DistKind::Small),
+            "zipf" => Ok(DistKind::Zipf),
+            "alternating-sign" => Ok(DistKind::AlternatingSign),
+            "max-magnitude" => Ok(DistKind::MaxMagnitude),
+            _ => Err(format!("Invalid random input distribution: {}", s)),
+        }
+    }
+}
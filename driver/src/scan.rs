@@ -4,18 +4,24 @@
 use std::fmt;
 use std::simd::LaneCount;
 use std::simd::MaskElement;
-use std::simd::Simd;
 use std::simd::SimdElement;
 use std::simd::SupportedLaneCount;
+use std::time::Duration;
+use std::time::Instant;
 
+use crate::element::ElementKind;
 use crate::exit::exit;
 use crate::exit::ExitCode;
 
 use bytemuck::Pod;
-use support::IAdd;
+use support::IBottom;
 use support::ICast;
 use support::IDisplay;
+use support::IOperator;
+use support::IOperatorSimd;
 use support::IScan;
+use support::IZero;
+use support::ScanKind;
 
 #[repr(i8)]
 #[derive(Clone, Copy, Eq, Hash, PartialEq, PartialOrd)]
@@ -26,107 +32,322 @@ pub enum ImplKind {
     SimdNaiveDoubleBuffer,
     ParallelCPUNaiveDoubleBuffer,
     ParallelGPUNaiveDoubleBuffer,
+    SequentialBlelloch,
+    ParallelCPUBlelloch,
+    ParallelCPULookback,
+    ParallelCPUReduceScan,
+    ParallelGPUBlelloch,
+}
+
+/// The scan function of a scan object, over an input and output vector of the same length.
+pub type ScanFn<'a, T> = dyn FnMut(&[T], &mut [T]) -> Result<(), String> + 'a;
+
+/// Run `f` `n_repeat` times (e.g., to benchmark repeated invocations of the same scan object),
+/// returning the elapsed time of each invocation.
+pub fn repeat<F>(n_repeat: usize, mut f: F) -> Result<Vec<Duration>, String>
+where
+    F: FnMut() -> Result<(), String>,
+{
+    let mut timing: Vec<Duration> = Vec::with_capacity(n_repeat);
+    for _ in 0..n_repeat {
+        let time_start = Instant::now();
+        f()?;
+        timing.push(time_start.elapsed());
+    }
+    Ok(timing)
 }
 
 impl ImplKind {
-    pub fn dispatch<T>(
+    pub const ALL: [ImplKind; 11] = [
+        ImplKind::Sequential,
+        ImplKind::SequentialNaive,
+        ImplKind::SequentialNaiveDoubleBuffer,
+        ImplKind::SimdNaiveDoubleBuffer,
+        ImplKind::ParallelCPUNaiveDoubleBuffer,
+        ImplKind::ParallelGPUNaiveDoubleBuffer,
+        ImplKind::SequentialBlelloch,
+        ImplKind::ParallelCPUBlelloch,
+        ImplKind::ParallelCPULookback,
+        ImplKind::ParallelCPUReduceScan,
+        ImplKind::ParallelGPUBlelloch,
+    ];
+
+    pub fn dispatch<T, O>(
         &self,
-        identity: T,
+        kind: ScanKind,
+        n_repeat: usize,
         v_in: &[T],
         v_out: &mut [T],
         verbose: bool,
-    ) -> Result<(), String>
+    ) -> Result<Vec<Duration>, String>
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        self.with_scan::<T, O, _, _>(kind, verbose, |scan| repeat(n_repeat, || scan(v_in, v_out)))
+    }
+
+    /// Build the scan object of the implementation once, and pass its scan function to `f`
+    /// (e.g., to scan several inputs with the same scan object).
+    pub fn with_scan<T, O, F, R>(&self, kind: ScanKind, verbose: bool, f: F) -> Result<R, String>
     where
-        T: Copy + Eq + IDisplay + IAdd,
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+        F: FnOnce(&mut ScanFn<'_, T>) -> Result<R, String>,
     {
         match self {
             ImplKind::Sequential => {
                 let scan_obj = seq::Scan::new(verbose);
-                scan_obj.process::<T>(identity, v_in, v_out)
+                f(&mut |v_in, v_out| scan_obj.process::<T, O>(kind, v_in, v_out))
             }
             ImplKind::SequentialNaive => {
                 let scan_obj = seq_naive::Scan::new(verbose);
-                scan_obj.process::<T>(identity, v_in, v_out)
+                f(&mut |v_in, v_out| scan_obj.process::<T, O>(kind, v_in, v_out))
             }
             ImplKind::SequentialNaiveDoubleBuffer => {
                 let scan_obj = seq_naive_dbl::Scan::new(verbose);
-                scan_obj.process::<T>(identity, v_in, v_out)
+                f(&mut |v_in, v_out| scan_obj.process::<T, O>(kind, v_in, v_out))
+            }
+            ImplKind::SequentialBlelloch => {
+                let scan_obj = seq_blelloch::Scan::new(verbose);
+                f(&mut |v_in, v_out| scan_obj.process::<T, O>(kind, v_in, v_out))
             }
             _ => {
                 let scan_obj = unimplemented::Scan::new(verbose);
-                scan_obj.process::<T>(identity, v_in, v_out)
+                f(&mut |v_in, v_out| scan_obj.process::<T, O>(kind, v_in, v_out))
             }
         }
     }
 
-    pub fn dispatch_simd<T, const N: usize>(
+    pub fn dispatch_simd<T, O, const N: usize>(
         &self,
-        identity: T,
+        kind: ScanKind,
+        n_repeat: usize,
         v_in: &[T],
         v_out: &mut [T],
         verbose: bool,
-    ) -> Result<(), String>
+    ) -> Result<Vec<Duration>, String>
     where
-        T: Copy + Eq + IAdd + IDisplay + SimdElement,
+        T: Copy + IDisplay + SimdElement,
         T::Mask: IDisplay + MaskElement,
-        Simd<T, N>: IAdd,
+        O: IOperatorSimd<T, N>,
         LaneCount<N>: SupportedLaneCount,
+    {
+        self.with_scan_simd::<T, O, N, _, _>(kind, verbose, |scan| {
+            repeat(n_repeat, || scan(v_in, v_out))
+        })
+    }
+
+    /// Build the scan object of the SIMD implementation once, and pass its scan function to `f`
+    /// (see `with_scan`).
+    pub fn with_scan_simd<T, O, const N: usize, F, R>(
+        &self,
+        kind: ScanKind,
+        verbose: bool,
+        f: F,
+    ) -> Result<R, String>
+    where
+        T: Copy + IDisplay + SimdElement,
+        T::Mask: IDisplay + MaskElement,
+        O: IOperatorSimd<T, N>,
+        LaneCount<N>: SupportedLaneCount,
+        F: FnOnce(&mut ScanFn<'_, T>) -> Result<R, String>,
     {
         match self {
             ImplKind::SimdNaiveDoubleBuffer => {
                 let scan_obj = simd_naive_dbl::Scan::new(verbose);
-                scan_obj.process::<T, N>(identity, v_in, v_out)
+                f(&mut |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out))
             }
             _ => {
                 let scan_obj = simd_unimplemented::Scan::new(verbose);
-                scan_obj.process::<T, N>(identity, v_in, v_out)
+                f(&mut |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out))
             }
         }
     }
 
-    pub fn dispatch_parallel<T, const N: usize>(
+    pub fn dispatch_segmented<T, O, const N: usize>(
         &self,
-        identity: T,
+        kind: ScanKind,
+        flags: &[bool],
+        n_repeat: usize,
         v_in: &[T],
         v_out: &mut [T],
         verbose: bool,
-    ) -> Result<(), String>
+    ) -> Result<Vec<Duration>, String>
     where
-        T: Copy + Eq + IAdd + ICast<i32> + IDisplay + Ord + Pod + Send,
-        i32: ICast<T>,
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
     {
         match self {
+            ImplKind::Sequential => {
+                let scan_obj = seq::Scan::new(verbose);
+                repeat(n_repeat, || {
+                    scan_obj.process_segmented::<T, O>(kind, flags, v_in, v_out)
+                })
+            }
+            ImplKind::SequentialNaiveDoubleBuffer => {
+                let scan_obj = seq_naive_dbl::Scan::new(verbose);
+                repeat(n_repeat, || {
+                    scan_obj.process_segmented::<T, O>(kind, flags, v_in, v_out)
+                })
+            }
             ImplKind::ParallelCPUNaiveDoubleBuffer => {
                 let scan_obj = par_cpu_naive_dbl::Scan::new(verbose);
-                scan_obj.process::<T, N>(identity, v_in, v_out)
+                repeat(n_repeat, || {
+                    scan_obj.process_segmented::<T, O, N>(kind, flags, v_in, v_out)
+                })
+            }
+            _ => {
+                let scan_obj = unimplemented::Scan::new(verbose);
+                repeat(n_repeat, || {
+                    scan_obj.process_segmented::<T, O>(kind, flags, v_in, v_out)
+                })
             }
+        }
+    }
+
+    pub fn dispatch_parallel<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        num_threads: Option<usize>,
+        n_repeat: usize,
+        v_in: &[T],
+        v_out: &mut [T],
+        verbose: bool,
+    ) -> Result<Vec<Duration>, String>
+    where
+        T: Copy + IBottom + ICast<i64> + IDisplay + IZero + Pod + Send + Sync,
+        O: IOperator<T>,
+        i64: ICast<T>,
+    {
+        match self {
             ImplKind::ParallelGPUNaiveDoubleBuffer => {
                 let scan_obj = par_gpu_naive_dbl::Scan::new(verbose);
-                scan_obj.process::<T, N>(identity, v_in, v_out)
+                repeat(n_repeat, || scan_obj.process::<T, O, N>(kind, v_in, v_out))
+            }
+            ImplKind::ParallelGPUBlelloch => {
+                let scan_obj = par_gpu_blelloch::Scan::new(verbose);
+                repeat(n_repeat, || scan_obj.process::<T, O, N>(kind, v_in, v_out))
             }
+            _ => self.dispatch_parallel_cpu::<T, O, N>(
+                kind,
+                num_threads,
+                n_repeat,
+                v_in,
+                v_out,
+                verbose,
+            ),
+        }
+    }
+
+    /// Dispatch to the parallel CPU implementations only, for element types without GPU support
+    /// (e.g., floating point types).
+    pub fn dispatch_parallel_cpu<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        num_threads: Option<usize>,
+        n_repeat: usize,
+        v_in: &[T],
+        v_out: &mut [T],
+        verbose: bool,
+    ) -> Result<Vec<Duration>, String>
+    where
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
+    {
+        self.with_scan_parallel_cpu::<T, O, N, _, _>(kind, num_threads, verbose, |scan| {
+            repeat(n_repeat, || scan(v_in, v_out))
+        })
+    }
+
+    /// Build the scan object of the parallel CPU implementation once, and pass its scan function
+    /// to `f` (see `with_scan`).
+    pub fn with_scan_parallel_cpu<T, O, const N: usize, F, R>(
+        &self,
+        kind: ScanKind,
+        num_threads: Option<usize>,
+        verbose: bool,
+        f: F,
+    ) -> Result<R, String>
+    where
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
+        F: FnOnce(&mut ScanFn<'_, T>) -> Result<R, String>,
+    {
+        match self {
+            ImplKind::ParallelCPUNaiveDoubleBuffer => {
+                let scan_obj = match num_threads {
+                    Some(num_threads) => {
+                        par_cpu_naive_dbl::Scan::with_num_threads(verbose, num_threads)
+                    }
+                    None => par_cpu_naive_dbl::Scan::new(verbose),
+                };
+                f(&mut |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out))
+            }
+            ImplKind::ParallelCPUBlelloch => {
+                let scan_obj = match num_threads {
+                    Some(num_threads) => {
+                        par_cpu_blelloch::Scan::with_num_threads(verbose, num_threads)
+                    }
+                    None => par_cpu_blelloch::Scan::new(verbose),
+                };
+                f(&mut |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out))
+            }
+            ImplKind::ParallelCPULookback => {
+                let scan_obj = match num_threads {
+                    Some(num_threads) => {
+                        par_cpu_lookback::Scan::with_num_threads(verbose, num_threads)
+                    }
+                    None => par_cpu_lookback::Scan::new(verbose),
+                };
+                f(&mut |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out))
+            }
+            ImplKind::ParallelCPUReduceScan => {
+                let scan_obj = match num_threads {
+                    Some(num_threads) => {
+                        par_cpu_reduce_scan::Scan::with_num_threads(verbose, num_threads)
+                    }
+                    None => par_cpu_reduce_scan::Scan::new(verbose),
+                };
+                f(&mut |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out))
+            }
+            ImplKind::ParallelGPUNaiveDoubleBuffer | ImplKind::ParallelGPUBlelloch => Err(format!(
+                "Expected integer element type for implementation {}",
+                self
+            )),
             _ => {
                 let scan_obj = par_unimplemented::Scan::new(verbose);
-                scan_obj.process::<T, N>(identity, v_in, v_out)
+                f(&mut |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out))
             }
         }
     }
 
     pub fn get_options_string() -> String {
         format!(
-            "Implementations:\n{}\n{}\n{}\n{}\n{}\n{}",
+            "Implementations:\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
             ImplKind::Sequential.to_option_string(),
             ImplKind::SequentialNaive.to_option_string(),
             ImplKind::SequentialNaiveDoubleBuffer.to_option_string(),
             ImplKind::SimdNaiveDoubleBuffer.to_option_string(),
             ImplKind::ParallelCPUNaiveDoubleBuffer.to_option_string(),
             ImplKind::ParallelGPUNaiveDoubleBuffer.to_option_string(),
+            ImplKind::SequentialBlelloch.to_option_string(),
+            ImplKind::ParallelCPUBlelloch.to_option_string(),
+            ImplKind::ParallelCPULookback.to_option_string(),
+            ImplKind::ParallelCPUReduceScan.to_option_string(),
+            ImplKind::ParallelGPUBlelloch.to_option_string(),
         )
     }
 
     pub fn is_parallel(self) -> bool {
         matches!(
             self,
-            ImplKind::ParallelCPUNaiveDoubleBuffer | ImplKind::ParallelGPUNaiveDoubleBuffer
+            ImplKind::ParallelCPUNaiveDoubleBuffer
+                | ImplKind::ParallelGPUNaiveDoubleBuffer
+                | ImplKind::ParallelCPUBlelloch
+                | ImplKind::ParallelCPULookback
+                | ImplKind::ParallelCPUReduceScan
+                | ImplKind::ParallelGPUBlelloch
         )
     }
 
@@ -134,6 +355,19 @@ impl ImplKind {
         self == ImplKind::SimdNaiveDoubleBuffer
     }
 
+    pub fn is_gpu(self) -> bool {
+        matches!(
+            self,
+            ImplKind::ParallelGPUNaiveDoubleBuffer | ImplKind::ParallelGPUBlelloch
+        )
+    }
+
+    /// Returns whether the implementation scans elements of type `element` (i.e., the GPU
+    /// implementations only scan integer types).
+    pub fn supports(self, element: ElementKind) -> bool {
+        !(self.is_gpu() && element.is_float())
+    }
+
     pub fn to_option_string(self) -> String {
         format!("*  {} => {}", self as i8, self)
     }
@@ -151,6 +385,11 @@ impl fmt::Display for ImplKind {
                 ImplKind::SimdNaiveDoubleBuffer => "ScanSimdNaive2x",
                 ImplKind::ParallelCPUNaiveDoubleBuffer => "ScanParCPUNaive2x",
                 ImplKind::ParallelGPUNaiveDoubleBuffer => "ScanParGPUNaive2x",
+                ImplKind::SequentialBlelloch => "ScanSeqBlelloch",
+                ImplKind::ParallelCPUBlelloch => "ScanParCPUBlelloch",
+                ImplKind::ParallelCPULookback => "ScanParCPULookback",
+                ImplKind::ParallelCPUReduceScan => "ScanParCPUReduceScan",
+                ImplKind::ParallelGPUBlelloch => "ScanParGPUBlelloch",
             }
         )
     }
@@ -165,6 +404,11 @@ impl From<i8> for ImplKind {
             3 => ImplKind::SimdNaiveDoubleBuffer,
             4 => ImplKind::ParallelCPUNaiveDoubleBuffer,
             5 => ImplKind::ParallelGPUNaiveDoubleBuffer,
+            6 => ImplKind::SequentialBlelloch,
+            7 => ImplKind::ParallelCPUBlelloch,
+            8 => ImplKind::ParallelCPULookback,
+            9 => ImplKind::ParallelCPUReduceScan,
+            10 => ImplKind::ParallelGPUBlelloch,
             _ => {
                 exit(
                     ExitCode::Error,
@@ -0,0 +1,582 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use test_scan::cli::gen_npy;
+    use test_scan::cli::test_body;
+    use test_scan::cli::test_body_args;
+    use test_scan::cli::test_body_command;
+    use test_scan::cli::test_body_command_error;
+    use test_scan::cli::test_body_contains;
+    use test_scan::cli::test_body_input;
+    use test_scan::cli::test_body_input_bytes;
+    use test_scan::gen_ramp_in;
+    use test_scan::gen_ramp_out;
+    use test_scan::golden::get_in;
+    use test_scan::golden::get_out;
+    use test_scan::golden::N15_1;
+    use test_scan::golden::N15_1_INCL;
+    use test_scan::golden::N16_2;
+    use test_scan::golden::N8_1;
+    use test_scan::golden::N8_1_INCL;
+    use test_scan::golden::N8_2;
+    use test_scan::golden::N8_4;
+    use test_scan::golden::N8_4_INCL;
+    use test_scan::golden::N8_4_MIN;
+    use test_scan::N16_1_IN;
+    use test_scan::N16_1_SEG_FLAGS;
+    use test_scan::N16_1_SEG_OFFSETS;
+    use test_scan::N16_1_SEG_OUT;
+
+    /// The `scan` binary built for the tests (in the profile and target directory of the tests).
+    const BIN: &str = env!("CARGO_BIN_EXE_scan");
+
+    /// Run the test of the implementation `impl_id` over the golden vector `N8_1`.
+    fn test_body_n8_1(impl_id: i8, impl_str: &str) {
+        test_body(
+            BIN,
+            impl_id,
+            impl_str,
+            &get_in::<i64>(N8_1),
+            &get_out::<i64>(N8_1),
+        );
+    }
+
+    #[test]
+    fn test_seq_n8_1() {
+        test_body_n8_1(0, "ScanSeq");
+    }
+
+    #[test]
+    fn test_seq_naive_n8_1() {
+        test_body_n8_1(1, "ScanSeqNaive");
+    }
+
+    #[test]
+    fn test_seq_naive_2x_n8_1() {
+        test_body_n8_1(2, "ScanSeqNaive2x");
+    }
+
+    #[test]
+    fn test_simd_naive_2x_n8_1() {
+        test_body_n8_1(3, "ScanSimdNaive2x");
+    }
+
+    #[test]
+    fn test_par_cpu_naive_2x_n8_1() {
+        test_body_n8_1(4, "ScanParCPUNaive2x");
+    }
+
+    #[test]
+    fn test_par_gpu_naive_2x_n8_1() {
+        test_body_n8_1(5, "ScanParGPUNaive2x");
+    }
+
+    #[test]
+    fn test_seq_blelloch_n8_1() {
+        test_body_n8_1(6, "ScanSeqBlelloch");
+    }
+
+    #[test]
+    fn test_par_cpu_blelloch_n8_1() {
+        test_body_n8_1(7, "ScanParCPUBlelloch");
+    }
+
+    #[test]
+    fn test_par_cpu_lookback_n8_1() {
+        test_body_n8_1(8, "ScanParCPULookback");
+    }
+
+    #[test]
+    fn test_par_cpu_reduce_scan_n8_1() {
+        test_body_n8_1(9, "ScanParCPUReduceScan");
+    }
+
+    #[test]
+    fn test_par_gpu_blelloch_n8_1() {
+        test_body_n8_1(10, "ScanParGPUBlelloch");
+    }
+
+    #[test]
+    fn test_simd_naive_2x_lanes_16_type_u8_n8_2() {
+        test_body_args(
+            BIN,
+            3,
+            "ScanSimdNaive2x",
+            &["--lanes=16", "--type=u8"],
+            &get_in::<u8>(N8_2),
+            &get_out::<u8>(N8_2),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_naive_2x_threads_2_repeat_3_inclusive_n15_1() {
+        test_body_args(
+            BIN,
+            4,
+            "ScanParCPUNaive2x",
+            &["--threads=2", "--repeat=3", "--inclusive"],
+            &get_in::<i64>(N15_1),
+            &get_out::<i64>(N15_1_INCL),
+        );
+    }
+
+    #[test]
+    fn test_seq_type_f64_min_n8_4() {
+        test_body_args(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--type=f64", "--op=min"],
+            &get_in::<f64>(N8_4),
+            &get_out::<f64>(N8_4_MIN),
+        );
+    }
+
+    #[test]
+    fn test_seq_segmented_n16_1() {
+        test_body_args(
+            BIN,
+            0,
+            "ScanSeq",
+            &[N16_1_SEG_FLAGS],
+            &N16_1_IN,
+            &N16_1_SEG_OUT,
+        );
+    }
+
+    #[test]
+    fn test_seq_segmented_offsets_n16_1() {
+        test_body_args(
+            BIN,
+            0,
+            "ScanSeq",
+            &[N16_1_SEG_OFFSETS],
+            &N16_1_IN,
+            &N16_1_SEG_OUT,
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_naive_2x_segmented_offsets_n16_1() {
+        test_body_args(
+            BIN,
+            4,
+            "ScanParCPUNaive2x",
+            &[N16_1_SEG_OFFSETS],
+            &N16_1_IN,
+            &N16_1_SEG_OUT,
+        );
+    }
+
+    #[test]
+    fn test_seq_accuracy_compensated_n8_4() {
+        test_body_args(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--type=f64", "--accuracy=compensated"],
+            &get_in::<f64>(N8_4),
+            &get_out::<f64>(N8_4),
+        );
+    }
+
+    #[test]
+    fn test_seq_accuracy_pairwise_inclusive_n8_4() {
+        test_body_args(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--type=f64", "--accuracy=pairwise", "--inclusive"],
+            &get_in::<f64>(N8_4),
+            &get_out::<f64>(N8_4_INCL),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_reduce_scan_accuracy_compensated_n8_4() {
+        test_body_args(
+            BIN,
+            9,
+            "ScanParCPUReduceScan",
+            &["--type=f64", "--accuracy=compensated", "--threads=3"],
+            &get_in::<f64>(N8_4),
+            &get_out::<f64>(N8_4),
+        );
+    }
+
+    #[test]
+    fn test_seq_input_stdin_n8_1() {
+        test_body_input(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--input=-"],
+            b"3, 1, 7\n0 4\n1,6,\t3\n",
+            &get_out::<i64>(N8_1),
+        );
+    }
+
+    #[test]
+    fn test_seq_input_file_inclusive_n8_1() {
+        let path = env::temp_dir().join("test_seq_input_file_inclusive_n8_1.txt");
+        let str_in: Vec<String> = get_in::<i64>(N8_1).iter().map(|x| x.to_string()).collect();
+        fs::write(&path, str_in.join("\n")).unwrap();
+        let arg_input = format!("--input={}", path.display());
+        test_body_input(
+            BIN,
+            0,
+            "ScanSeq",
+            &[&arg_input, "--inclusive"],
+            &[],
+            &get_out::<i64>(N8_1_INCL),
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_seq_input_binary_type_f64_n8_4() {
+        let data: Vec<u8> = get_in::<f64>(N8_4)
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        test_body_input(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--input=-", "--binary", "--type=f64"],
+            &data,
+            &get_out::<f64>(N8_4),
+        );
+    }
+
+    #[test]
+    fn test_seq_input_stdin_n65536_ramp() {
+        // NOTE: Too long for the command line, so the input is read from stdin
+        let n = 1 << 16;
+        let str_in: Vec<String> = gen_ramp_in(n).iter().map(|x| x.to_string()).collect();
+        test_body_input(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--input=-"],
+            str_in.join(" ").as_bytes(),
+            &gen_ramp_out(n),
+        );
+    }
+
+    #[test]
+    fn test_seq_input_npy_type_u8_n8_2() {
+        test_body_input(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--input=-"],
+            &gen_npy("|u1", 8, &get_in::<u8>(N8_2)),
+            &get_out::<u8>(N8_2),
+        );
+    }
+
+    #[test]
+    fn test_seq_input_npy_type_f64_inclusive_n8_4() {
+        let data: Vec<u8> = get_in::<f64>(N8_4)
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        test_body_input(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--input=-", "--inclusive"],
+            &gen_npy("<f8", 8, &data),
+            &get_out::<f64>(N8_4_INCL),
+        );
+    }
+
+    #[test]
+    fn test_seq_output_npy_n16_2() {
+        let data_in: Vec<u8> = get_in::<i64>(N16_2)
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let data_out: Vec<u8> = get_out::<i64>(N16_2)
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        test_body_input_bytes(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--input=-", "--output=-"],
+            &gen_npy("<i8", 16, &data_in),
+            &gen_npy("<i8", 16, &data_out),
+        );
+    }
+
+    #[test]
+    fn test_seq_format_json_n8_1() {
+        test_body_contains(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--format=json"],
+            &get_in::<i64>(N8_1),
+            &[
+                "{\"impl\": \"ScanSeq\", \"type\": \"i64\", \"n\": 8, \"repeat\": 1,",
+                "\"output\": [0, 3, 4, 11, 11, 15, 16, 22]}",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_format_json_type_f64_min_n8_4() {
+        test_body_contains(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--format=json", "--type=f64", "--op=min"],
+            &get_in::<f64>(N8_4),
+            &["\"output\": [Infinity, 1.5, 1.5, -0.5, -0.5, -0.5, -4, -4]}"],
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_reduce_scan_format_json_n8_1() {
+        test_body_contains(
+            BIN,
+            9,
+            "ScanParCPUReduceScan",
+            &["--format=json", "--repeat=4"],
+            &get_in::<i64>(N8_1),
+            &[
+                "{\"impl\": \"ScanParCPUReduceScan\", \"type\": \"i64\", \"n\": 8,",
+                "\"repeat\": 4,",
+                "\"output\": [0, 3, 4, 11, 11, 15, 16, 22]}",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_format_csv_inclusive_n8_1() {
+        test_body_contains(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--format=csv", "--inclusive"],
+            &get_in::<i64>(N8_1),
+            &["index,in,out\n0,3,3\n1,1,4\n2,7,11\n3,0,11\n4,4,15\n5,1,16\n6,6,22\n7,3,25\n"],
+        );
+    }
+
+    #[test]
+    fn test_seq_format_binary_type_u8_n8_2() {
+        test_body_input_bytes(
+            BIN,
+            0,
+            "ScanSeq",
+            &["--input=-", "--binary", "--format=binary", "--type=u8"],
+            &get_in::<u8>(N8_2),
+            &get_out::<u8>(N8_2),
+        );
+    }
+
+    #[test]
+    fn test_seq_bench() {
+        test_body_command(
+            BIN,
+            &[
+                "bench",
+                "--sizes=1000,4096",
+                "--warmup=1",
+                "--repeat=3",
+                "0",
+            ],
+            &["median", "elem/s", "ScanSeq ", " 1000 ", " 4096 "],
+        );
+    }
+
+    #[test]
+    fn test_seq_bench_all() {
+        test_body_command(
+            BIN,
+            &["bench", "--sizes=1000", "--warmup=1", "--repeat=2"],
+            &[
+                "ScanSeq ",
+                "ScanSimdNaive2x ",
+                "ScanParCPUReduceScan ",
+                "ScanParGPUBlelloch ",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_n8() {
+        test_body_command(
+            BIN,
+            &["check", "8", "3", "1", "7", "0", "4", "1", "6", "3"],
+            &[
+                "ScanSeq                reference",
+                "ScanSeqBlelloch        ok",
+                "ScanParCPUReduceScan   ok",
+                "ScanParGPUBlelloch     ok",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_flags_n4() {
+        test_body_command(
+            BIN,
+            &["--flags=1,0,1,0", "check", "4", "1", "2", "3", "4"],
+            &[
+                "ScanSeqNaive2x         ok",
+                "ScanSeqBlelloch        skipped (Unimplemented)",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_random_inclusive_type_u16() {
+        test_body_command(
+            BIN,
+            &["--random=5000", "--inclusive", "--type=u16", "check"],
+            &["ScanParCPULookback     ok", "ScanParGPUNaive2x      ok"],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_mismatch_type_f32() {
+        test_body_command_error(
+            BIN,
+            &["--type=f32", "check", "4", "3e38", "3e38", "-3e38", "0"],
+            &[
+                "ScanSeqNaive           mismatch at index 3 (expected inf, found",
+                "implementations disagreeing with ScanSeq (N=4)",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_rounding_type_f32() {
+        // NOTE: Reordered sums only differ by rounding, within the tolerance of the check
+        test_body_command(
+            BIN,
+            &[
+                "--type=f32",
+                "check",
+                "6",
+                "1e8",
+                "1",
+                "-1e8",
+                "0.5",
+                "0.25",
+                "3",
+            ],
+            &["ScanSeqNaive           ok", "ScanSeqBlelloch        ok"],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_random_type_f32() {
+        test_body_command(
+            BIN,
+            &["--random=2000", "--type=f32", "check"],
+            &[
+                "ScanSeqNaive           ok",
+                "ScanParGPUNaive2x      skipped (Unsupported element type f32)",
+                "ScanParGPUBlelloch     skipped (Unsupported element type f32)",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_minimize_type_f32() {
+        test_body_command_error(
+            BIN,
+            &["--type=f32", "check", "4", "3e38", "3e38", "-3e38", "0"],
+            &[
+                "test-scan/vectors/n4_repro_seq_naive.txt:",
+                "# Exclusive add scan of N4_REPRO_SEQ_NAIVE (4 elements of type f32)",
+                "op = add\ntype = f32\nkind = exclusive\n",
+                "in = 7.5e37 3e38 -3.75e37 0.0\nout = 0.0 7.5e37 inf inf\n",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_check_minimize_flags_type_f32() {
+        test_body_command_error(
+            BIN,
+            &[
+                "--type=f32",
+                "--flags=1,0,0,0",
+                "check",
+                "4",
+                "3e38",
+                "3e38",
+                "-3e38",
+                "0",
+            ],
+            &[
+                "pub const N4_REPRO_IN: [f32; 4] = [7.5e37, 3e38, -3.75e37, 0.0];",
+                "pub const N4_REPRO_OUT: [f32; 4] = [0.0, 7.5e37, f32::INFINITY, f32::INFINITY];",
+                "fn test_seq_naive_2x_repro_n4() {",
+                "scan_segmented::<_, OpAdd>(ScanKind::Exclusive, &[true, false, false, false])",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_random_small_type_i8() {
+        test_body_command(
+            BIN,
+            &["--random=8", "--seed=42", "--dist=small", "--type=i8", "0"],
+            &[
+                "in  : [3, -6, -4, -3, -8, 5, -5, 4]",
+                "out : [0, 3, -3, -7, -10, -18, -13, -18]",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_random_max_magnitude_inclusive_type_i16() {
+        test_body_command(
+            BIN,
+            &[
+                "--random=6",
+                "--seed=7",
+                "--dist=max-magnitude",
+                "--type=i16",
+                "--inclusive",
+                "0",
+            ],
+            &[
+                "in  : [32767, 32767, -32768, -32768, 32767, 32767]",
+                "out : [32767, -2, 32766, -2, 32765, -4]",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_seq_random_alternating_sign_inclusive_type_u8() {
+        test_body_command(
+            BIN,
+            &[
+                "--random=6",
+                "--seed=7",
+                "--dist=alternating-sign",
+                "--type=u8",
+                "--inclusive",
+                "0",
+            ],
+            &[
+                "in  : [6, 0, 14, 247, 7, 253]",
+                "out : [6, 6, 20, 11, 18, 15]",
+            ],
+        );
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#![allow(clippy::unused_unit)]
+
+use std::marker::Send;
+use std::thread;
+
+use support::alloc;
+use support::copy;
+use support::IDisplay;
+use support::IOperator;
+use support::IScan;
+use support::ScanKind;
+
+#[derive(Clone, Copy)]
+pub struct Scan {
+    verbose: bool,
+    num_threads: usize,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum SweepPhase {
+    Up,
+    Down,
+}
+
+#[derive(Copy, Clone)]
+pub struct WorkGroup {
+    verbose: bool,
+    id: usize,
+    offset: usize,
+    phase: SweepPhase,
+}
+
+impl Scan {
+    /// Create a scan object running on a fixed number of worker threads, regardless of the
+    /// input length.
+    pub fn with_num_threads(verbose: bool, num_threads: usize) -> Self {
+        Self {
+            verbose,
+            num_threads,
+        }
+    }
+
+    pub fn get_num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Run one depth of the sweep `phase` over the tree, split into chunks of `N` whole subtrees
+    /// (i.e., `N` nodes at the current depth). Each worker thread strides over the chunks, so that
+    /// the number of threads is bounded by `num_threads` at every depth.
+    fn sweep<T, O, const N: usize>(&self, tree: &mut [T], offset: usize, phase: SweepPhase) -> ()
+    where
+        T: Copy + IDisplay + Send,
+        O: IOperator<T>,
+    {
+        let stride = offset << 1;
+        let n_chunk = N * stride;
+        let n_workers = usize::min(self.num_threads, usize::div_ceil(tree.len(), n_chunk));
+        let mut chunks_worker: Vec<Vec<(usize, &mut [T])>> =
+            (0..n_workers).map(|_| Vec::new()).collect();
+        for (k, chunk) in tree.chunks_mut(n_chunk).enumerate() {
+            chunks_worker[k % n_workers].push((k, chunk));
+        }
+        let verbose = self.verbose;
+        thread::scope(|s| {
+            for (id, chunks) in chunks_worker.into_iter().enumerate() {
+                s.spawn(move || {
+                    let workgroup = WorkGroup {
+                        verbose,
+                        id,
+                        offset,
+                        phase,
+                    };
+                    for (k, chunk) in chunks {
+                        if verbose {
+                            eprintln!("[{}] Chunk {}:", id, k);
+                        }
+                        workgroup.process::<T, O>(chunk);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Implement the parallel CPU work-efficient (Blelloch) exclusive/inclusive scan algorithm
+    pub fn process<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IDisplay + Send,
+        O: IOperator<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        Self::check_args(n_in, n_out)?;
+        if N == 0 {
+            return Err("Expected non-zero number of nodes per chunk".to_string());
+        }
+        if self.num_threads == 0 {
+            return Err("Expected non-zero number of worker threads".to_string());
+        }
+        // NOTE: Pad the tree to the next power of two with the identity element
+        let n_tree = n_out.next_power_of_two();
+        let d_end = n_tree.trailing_zeros() as usize;
+        let mut tree = alloc(n_tree, O::identity());
+        copy(v_in, &mut tree[0..n_out])?;
+        if self.verbose {
+            eprintln!(
+                "[_] Computing tree depth [0..{}) for {} leaves",
+                d_end, n_tree
+            );
+            eprintln!("[_] Up-sweep:");
+        }
+        for d in 0..d_end {
+            if self.verbose {
+                eprintln!("[_] Depth {}:", d);
+            }
+            self.sweep::<T, O, N>(&mut tree, 1 << d, SweepPhase::Up);
+        }
+        tree[n_tree - 1] = O::identity();
+        if self.verbose {
+            eprintln!("[_] Down-sweep:");
+        }
+        for d in (0..d_end).rev() {
+            if self.verbose {
+                eprintln!("[_] Depth {}:", d);
+            }
+            self.sweep::<T, O, N>(&mut tree, 1 << d, SweepPhase::Down);
+        }
+        match kind {
+            ScanKind::Exclusive => copy(&tree[0..n_out], v_out)?,
+            ScanKind::Inclusive => {
+                for k in 0..n_out {
+                    v_out[k] = O::combine(tree[k], v_in[k]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl IScan for Scan {
+    fn new(verbose: bool) -> Self {
+        let num_threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_num_threads(verbose, num_threads)
+    }
+}
+
+impl WorkGroup {
+    pub fn process<T, O>(&self, chunk: &mut [T]) -> ()
+    where
+        T: Copy + IDisplay + Send,
+        O: IOperator<T>,
+    {
+        let stride = self.offset << 1;
+        for k in ((stride - 1)..chunk.len()).step_by(stride) {
+            let j = k - self.offset;
+            match self.phase {
+                SweepPhase::Up => {
+                    let a = chunk[j];
+                    let b = chunk[k];
+                    if self.verbose {
+                        eprintln!("[{}] *   ({},{},{}): {} + {}", self.id, k, j, k, a, b);
+                    }
+                    chunk[k] = O::combine(a, b);
+                }
+                SweepPhase::Down => {
+                    let a = chunk[k];
+                    let b = chunk[j];
+                    if self.verbose {
+                        eprintln!("[{}] *   ({},{},{}): {} + {}", self.id, k, k, j, a, b);
+                    }
+                    chunk[j] = a;
+                    chunk[k] = O::combine(a, b);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#[cfg(test)]
+mod tests {
+    use support::OpAdd;
+    use support::ScanKind;
+    use test_scan::golden::get_in;
+    use test_scan::golden::get_out;
+    use test_scan::golden::N100_1;
+    use test_scan::golden::N15_1;
+    use test_scan::golden::N15_1_INCL;
+    use test_scan::golden_tests;
+    use test_scan::prop::test_prop_fold;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
+    use test_scan::test_body;
+
+    scan_fns!(par_cpu, par_cpu_blelloch::Scan; scan, scan_threads);
+
+    golden_tests!("ScanParCPUBlelloch", scan, 4);
+
+    prop_tests!("ScanParCPUBlelloch", scan, 4);
+
+    #[test]
+    fn test_par_cpu_blelloch_threads_3_n100_1() {
+        test_body(
+            "ScanParCPUBlelloch",
+            scan_threads::<_, OpAdd, 4>(ScanKind::Exclusive, 3),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_blelloch_threads_1_inclusive_n15_1() {
+        test_body(
+            "ScanParCPUBlelloch",
+            scan_threads::<_, OpAdd, 4>(ScanKind::Inclusive, 1),
+            &get_in::<i64>(N15_1),
+            &get_out::<i64>(N15_1_INCL),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_blelloch_threads_1_prop_fold() {
+        test_prop_fold::<i64, OpAdd, _, _>(
+            "ScanParCPUBlelloch",
+            4,
+            scan_threads::<i64, OpAdd, 4>(ScanKind::Exclusive, 1),
+            scan_threads::<i64, OpAdd, 4>(ScanKind::Inclusive, 1),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_blelloch_threads_3_prop_fold() {
+        test_prop_fold::<i64, OpAdd, _, _>(
+            "ScanParCPUBlelloch",
+            4,
+            scan_threads::<i64, OpAdd, 4>(ScanKind::Exclusive, 3),
+            scan_threads::<i64, OpAdd, 4>(ScanKind::Inclusive, 3),
+        );
+    }
+}
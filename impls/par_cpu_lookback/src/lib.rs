@@ -0,0 +1,365 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#![allow(clippy::unused_unit)]
+
+use std::cell::Cell;
+use std::cell::UnsafeCell;
+use std::cmp;
+use std::fmt;
+use std::hint;
+use std::marker::Send;
+use std::marker::Sync;
+use std::panic;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+use support::IDisplay;
+use support::IOperator;
+use support::IScan;
+use support::ScanKind;
+
+/// Default number of elements of a tile, rounded up to a multiple of the number of lanes `N`.
+pub const TILE_SIZE: usize = 4096;
+
+#[derive(Clone, Copy)]
+pub struct Scan {
+    verbose: bool,
+    num_threads: usize,
+    tile_size: usize,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+pub enum TileStatus {
+    #[default]
+    NotReady = 0,
+    AggregateAvailable = 1,
+    PrefixAvailable = 2,
+}
+
+/// The status of a tile published to its successors.
+/// The aggregate (resp. inclusive prefix) is written exactly once by the worker owning the tile,
+/// before the status is raised to `AggregateAvailable` (resp. `PrefixAvailable`) with release
+/// ordering.
+/// Successors only read the aggregate (resp. inclusive prefix) after observing the raised status
+/// with acquire ordering.
+pub struct TileDescriptor<T> {
+    status: AtomicU8,
+    aggregate: UnsafeCell<T>,
+    prefix: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for TileDescriptor<T> {}
+
+/// The output chunk of a tile, handed out (without a lock) to the single worker acquiring the
+/// tile from the tile counter.
+pub struct TileOutput<'a, T> {
+    chunk: Cell<Option<&'a mut [T]>>,
+}
+
+unsafe impl<T: Send> Sync for TileOutput<'_, T> {}
+
+#[derive(Copy, Clone)]
+pub struct WorkGroup<'a> {
+    verbose: bool,
+    id: usize,
+    kind: ScanKind,
+    n_tile: usize,
+    aborted: &'a AtomicBool,
+}
+
+impl Scan {
+    /// Create a scan object running on at most `num_threads` worker threads.
+    pub fn with_num_threads(verbose: bool, num_threads: usize) -> Self {
+        Self::with_tile_size(verbose, num_threads, TILE_SIZE)
+    }
+
+    /// Create a scan object running on at most `num_threads` worker threads, over tiles of
+    /// `tile_size` elements (rounded up to a multiple of the number of lanes).
+    pub fn with_tile_size(verbose: bool, num_threads: usize, tile_size: usize) -> Self {
+        Self {
+            verbose,
+            num_threads,
+            tile_size,
+        }
+    }
+
+    pub fn get_num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    pub fn get_tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// Implement the single-pass parallel CPU exclusive/inclusive scan algorithm using
+    /// decoupled look-back over tiles of `tile_size` elements, made of whole groups of `N` lanes
+    pub fn process<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        Self::check_args(n_in, n_out)?;
+        if N == 0 {
+            return Err("Expected non-zero number of lanes per tile".to_string());
+        }
+        if self.tile_size == 0 {
+            return Err("Expected non-zero tile size".to_string());
+        }
+        if self.num_threads == 0 {
+            return Err("Expected non-zero number of worker threads".to_string());
+        }
+        let n_tile = usize::div_ceil(self.tile_size, N) * N;
+        let n_tiles = usize::div_ceil(n_out, n_tile);
+        let n_threads = cmp::min(n_tiles, self.num_threads);
+        if self.verbose {
+            eprintln!(
+                "[_] Scanning {} tiles of size {} with {} worker threads",
+                n_tiles, n_tile, n_threads
+            );
+        }
+        let tiles = (0..n_tiles)
+            .map(|_| TileDescriptor::new(O::identity()))
+            .collect::<Vec<TileDescriptor<T>>>();
+        let tiles_out = v_out
+            .chunks_mut(n_tile)
+            .map(TileOutput::new)
+            .collect::<Vec<TileOutput<T>>>();
+        // NOTE: Tiles are acquired in order so that every predecessor of a tile has been
+        // acquired by a running worker, guaranteeing forward progress of the look-back.
+        let tile_counter = AtomicUsize::new(0);
+        // NOTE: Raised by a failing worker, so that the workers looking back over a tile that
+        // will never be published give up instead of spinning forever.
+        let aborted = &AtomicBool::new(false);
+        thread::scope(|s| {
+            let workers = (0..n_threads)
+                .map(|id| {
+                    let workgroup = WorkGroup {
+                        verbose: self.verbose,
+                        id,
+                        kind,
+                        n_tile,
+                        aborted,
+                    };
+                    let tiles = &tiles;
+                    let tiles_out = &tiles_out;
+                    let tile_counter = &tile_counter;
+                    s.spawn(move || loop {
+                        let i = tile_counter.fetch_add(1, Ordering::Relaxed);
+                        if i >= n_tiles {
+                            break Ok(());
+                        }
+                        let result = match tiles_out[i].take() {
+                            Some(tile_out) => panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                                workgroup.process::<T, O>(i, tiles, v_in, tile_out)
+                            }))
+                            .unwrap_or_else(|_| {
+                                Err(format!("Worker thread {} panicked at tile {}", id, i))
+                            }),
+                            None => Err(format!("Worker thread {} found no output tile {}", id, i)),
+                        };
+                        if result.is_err() {
+                            aborted.store(true, Ordering::Relaxed);
+                            break result;
+                        }
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|_| Err("Failed to join worker thread".to_string()))
+                })
+                .fold(Ok(()), Result::and)
+        })
+    }
+}
+
+impl IScan for Scan {
+    fn new(verbose: bool) -> Self {
+        let num_threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_num_threads(verbose, num_threads)
+    }
+}
+
+impl<T> TileDescriptor<T>
+where
+    T: Copy,
+{
+    fn new(def: T) -> Self {
+        Self {
+            status: AtomicU8::new(TileStatus::NotReady as u8),
+            aggregate: UnsafeCell::new(def),
+            prefix: UnsafeCell::new(def),
+        }
+    }
+
+    fn get_status(&self) -> TileStatus {
+        TileStatus::from(self.status.load(Ordering::Acquire))
+    }
+
+    /// Only valid once the status is at least `AggregateAvailable`
+    fn get_aggregate(&self) -> T {
+        unsafe { *self.aggregate.get() }
+    }
+
+    /// Only valid once the status is `PrefixAvailable`
+    fn get_prefix(&self) -> T {
+        unsafe { *self.prefix.get() }
+    }
+
+    /// Only valid for the worker owning the tile, and at most once
+    fn publish_aggregate(&self, aggregate: T) -> () {
+        unsafe { *self.aggregate.get() = aggregate };
+        self.status
+            .store(TileStatus::AggregateAvailable as u8, Ordering::Release);
+    }
+
+    /// Only valid for the worker owning the tile, and at most once
+    fn publish_prefix(&self, prefix: T) -> () {
+        unsafe { *self.prefix.get() = prefix };
+        self.status
+            .store(TileStatus::PrefixAvailable as u8, Ordering::Release);
+    }
+}
+
+impl<'a, T> TileOutput<'a, T> {
+    fn new(chunk: &'a mut [T]) -> Self {
+        Self {
+            chunk: Cell::new(Some(chunk)),
+        }
+    }
+
+    /// Only valid for the worker owning the tile (i.e., having acquired it from the tile counter)
+    fn take(&self) -> Option<&'a mut [T]> {
+        self.chunk.take()
+    }
+}
+
+impl WorkGroup<'_> {
+    /// Look back over the predecessors of tile `i` and return its exclusive prefix, or an error
+    /// if another worker failed before publishing a predecessor.
+    fn look_back<T, O>(&self, i: usize, tiles: &[TileDescriptor<T>]) -> Result<T, String>
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        let mut prefix = O::identity();
+        for j in (0..i).rev() {
+            loop {
+                match tiles[j].get_status() {
+                    TileStatus::NotReady => {
+                        if self.aborted.load(Ordering::Relaxed) {
+                            return Err(format!(
+                                "Worker thread {} aborted the look-back of tile {} at tile {}",
+                                self.id, i, j
+                            ));
+                        }
+                        hint::spin_loop();
+                    }
+                    TileStatus::AggregateAvailable => {
+                        let aggregate = tiles[j].get_aggregate();
+                        if self.verbose {
+                            eprintln!("[{}] *   ({},{}): aggregate {}", self.id, i, j, aggregate);
+                        }
+                        prefix = O::combine(aggregate, prefix);
+                        break;
+                    }
+                    TileStatus::PrefixAvailable => {
+                        let prefix_j = tiles[j].get_prefix();
+                        if self.verbose {
+                            eprintln!("[{}] *   ({},{}): prefix {}", self.id, i, j, prefix_j);
+                        }
+                        return Ok(O::combine(prefix_j, prefix));
+                    }
+                }
+            }
+        }
+        Ok(prefix)
+    }
+
+    pub fn process<T, O>(
+        &self,
+        i: usize,
+        tiles: &[TileDescriptor<T>],
+        v_in: &[T],
+        tile_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        let k_begin = i * self.n_tile;
+        let k_end = k_begin + tile_out.len();
+        let tile_in = &v_in[k_begin..k_end];
+        let aggregate = tile_in
+            .iter()
+            .fold(O::identity(), |acc, &x| O::combine(acc, x));
+        if self.verbose {
+            eprintln!(
+                "[{}] Tile {} [{}..{}): aggregate {}",
+                self.id, i, k_begin, k_end, aggregate
+            );
+        }
+        let prefix = if i == 0 {
+            tiles[i].publish_prefix(aggregate);
+            O::identity()
+        } else {
+            tiles[i].publish_aggregate(aggregate);
+            let prefix = self.look_back::<T, O>(i, tiles)?;
+            tiles[i].publish_prefix(O::combine(prefix, aggregate));
+            prefix
+        };
+        let mut acc = prefix;
+        for (k, &x) in tile_in.iter().enumerate() {
+            match self.kind {
+                ScanKind::Exclusive => {
+                    tile_out[k] = acc;
+                    acc = O::combine(acc, x);
+                }
+                ScanKind::Inclusive => {
+                    acc = O::combine(acc, x);
+                    tile_out[k] = acc;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<u8> for TileStatus {
+    fn from(n: u8) -> Self {
+        match n {
+            1 => TileStatus::AggregateAvailable,
+            2 => TileStatus::PrefixAvailable,
+            _ => TileStatus::NotReady,
+        }
+    }
+}
+
+impl fmt::Display for TileStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TileStatus::NotReady => "NotReady",
+                TileStatus::AggregateAvailable => "AggregateAvailable",
+                TileStatus::PrefixAvailable => "PrefixAvailable",
+            }
+        )
+    }
+}
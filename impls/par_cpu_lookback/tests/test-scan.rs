@@ -0,0 +1,125 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#[cfg(test)]
+mod tests {
+    use support::IDisplay;
+    use support::IOperator;
+    use support::OpAdd;
+    use support::OperatorKind;
+    use support::ScanKind;
+    use test_scan::gen_ramp_in;
+    use test_scan::gen_ramp_out;
+    use test_scan::golden::get_in;
+    use test_scan::golden::get_out;
+    use test_scan::golden::N100_1;
+    use test_scan::golden::N100_1_INCL;
+    use test_scan::golden_tests;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
+    use test_scan::test_body;
+
+    scan_fns!(par_cpu, par_cpu_lookback::Scan; scan, scan_threads);
+
+    /// Returns the scan of a new scan object with `num_threads` worker threads, over tiles of
+    /// `tile_size` elements.
+    fn scan_tiles<T, O, const N: usize>(
+        kind: ScanKind,
+        num_threads: usize,
+        tile_size: usize,
+    ) -> impl FnMut(&[T], &mut [T]) -> Result<(), String>
+    where
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
+    {
+        let scan_obj = par_cpu_lookback::Scan::with_tile_size(false, num_threads, tile_size);
+        move |v_in, v_out| scan_obj.process::<T, O, N>(kind, v_in, v_out)
+    }
+
+    /// An addition panicking on the element `-1` (e.g., to fail a single worker thread).
+    struct OpAddPanicking;
+
+    impl IOperator<i64> for OpAddPanicking {
+        const KIND: OperatorKind = OperatorKind::Add;
+
+        fn combine(a: i64, b: i64) -> i64 {
+            assert_ne!(b, -1, "Found poisoned element");
+            a + b
+        }
+
+        fn identity() -> i64 {
+            0
+        }
+    }
+
+    golden_tests!("ScanParCPULookback", scan, 4);
+
+    prop_tests!("ScanParCPULookback", scan, 4);
+
+    #[test]
+    fn test_par_cpu_lookback_lanes_1_n100_1() {
+        test_body(
+            "ScanParCPULookback",
+            scan::<_, OpAdd, 1>(ScanKind::Exclusive),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_lookback_tile_size_5_threads_3_n100_1() {
+        test_body(
+            "ScanParCPULookback",
+            scan_tiles::<_, OpAdd, 4>(ScanKind::Exclusive, 3, 5),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_lookback_tile_size_1_threads_1_inclusive_n100_1() {
+        test_body(
+            "ScanParCPULookback",
+            scan_tiles::<_, OpAdd, 1>(ScanKind::Inclusive, 1, 1),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1_INCL),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_lookback_threads_4_n100000_ramp() {
+        test_body(
+            "ScanParCPULookback",
+            scan_threads::<_, OpAdd, 4>(ScanKind::Exclusive, 4),
+            &gen_ramp_in(100_000),
+            &gen_ramp_out(100_000),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_lookback_threads_0() {
+        let scan_obj = par_cpu_lookback::Scan::with_num_threads(false, 0);
+        let mut v_out = [0; 100];
+        let result = scan_obj.process::<i64, OpAdd, 4>(
+            ScanKind::Exclusive,
+            &get_in::<i64>(N100_1),
+            &mut v_out,
+        );
+        assert_eq!(
+            result,
+            Err("Expected non-zero number of worker threads".to_string())
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_lookback_worker_panic_aborts() {
+        // NOTE: The successors of the failed tile must give up their look-back instead of hanging
+        let scan_obj = par_cpu_lookback::Scan::with_tile_size(false, 4, 2);
+        let mut v_in = gen_ramp_in(1000);
+        v_in[500] = -1;
+        let mut v_out = vec![0; 1000];
+        let result =
+            scan_obj.process::<i64, OpAddPanicking, 1>(ScanKind::Exclusive, &v_in, &mut v_out);
+        assert!(result.is_err());
+    }
+}
@@ -1,40 +1,86 @@
 // Copyright 2025, Giordano Salvador
 // SPDX-License-Identifier: BSD-3-Clause
 
-#![allow(static_mut_refs)]
 #![allow(clippy::unused_unit)]
 
 use std::cmp;
 use std::fmt;
+use std::marker::PhantomData;
 use std::marker::Send;
-use std::mem::size_of;
+use std::marker::Sync;
+use std::mem;
+use std::panic;
 use std::slice;
 use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 use std::sync::Barrier;
 use std::sync::Mutex;
 use std::thread;
 
-use support::align;
+use support::alloc;
+use support::alloc_aligned;
 use support::copy;
+use support::copy_scan_input;
+use support::segment;
+use support::unsegment;
 use support::DoubleBufferMode;
-use support::IAdd;
+use support::Flagged;
 use support::IDisplay;
+use support::IOperator;
 use support::IScan;
+use support::OpSegmented;
+use support::ScanKind;
 
-const NUM_PAGES: usize = 10;
-const PAGE_SIZE: usize = 4096;
-const BUFFER_LENGTH: usize = NUM_PAGES * PAGE_SIZE;
-static mut BUF_A: [u8; BUFFER_LENGTH] = [0_u8; BUFFER_LENGTH];
-static mut BUF_B: [u8; BUFFER_LENGTH] = [0_u8; BUFFER_LENGTH];
-static mut WORKGROUP_BARRIER: Option<Arc<Barrier>> = None;
-static mut WORKGROUP_STATUS: Option<Arc<Mutex<WorkStatus>>> = None;
-
-#[derive(Clone, Copy)]
 pub struct Scan {
     verbose: bool,
+    pool: Mutex<WorkerPool>,
+}
+
+/// The work run by every worker thread of the pool for a single depth,
+/// called as `task(worker_id, offset, mode)`.
+pub type Task<'a> = dyn Fn(usize, usize, DoubleBufferMode) + Sync + 'a;
+
+/// A pointer to the task of the current scan, valid while the scan is in progress.
+struct TaskPtr(*const Task<'static>);
+
+unsafe impl Send for TaskPtr {}
+
+/// The state shared between the owner of the pool and the worker threads.
+struct WorkerPoolState {
+    barrier: Barrier,
+    status: Mutex<WorkStatus>,
+    task: Mutex<Option<TaskPtr>>,
+}
+
+/// A pool of long-lived worker threads, reused across scans and shut down on drop.
+/// The worker threads are driven by the work status protocol: for every depth, the owner of the
+/// pool publishes the status `WorkPresent` and waits for every worker to acknowledge the
+/// beginning and end of its work.
+pub struct WorkerPool {
+    verbose: bool,
+    state: Arc<WorkerPoolState>,
+    workers: Vec<thread::JoinHandle<()>>,
+    ch_ack_received: Receiver<()>,
+    ch_ack_completed: Receiver<Result<(), String>>,
 }
 
+/// The pair of per-call buffers shared between the worker threads.
+/// For every depth, each worker only reads the source buffer and only writes its own (disjoint)
+/// chunk of the destination buffer.
+/// The depths are separated by the work status protocol, so that no element is read and written
+/// concurrently.
+pub struct DoubleBuffer<'a, T> {
+    n: usize,
+    buf_a: *mut T,
+    buf_b: *mut T,
+    _buffers: PhantomData<&'a mut [T]>,
+}
+
+// NOTE: Every worker reads the whole source buffer concurrently, hence `T: Sync` for `Sync`.
+unsafe impl<T: Send> Send for DoubleBuffer<'_, T> {}
+unsafe impl<T: Send + Sync> Sync for DoubleBuffer<'_, T> {}
+
 #[derive(Copy, Clone)]
 pub struct WorkGroup<const N: usize> {
     verbose: bool,
@@ -59,19 +105,16 @@ macro_rules! thread_body {
     (
         $Verbose:ident,
         $Id:ident,
-        $NIn:ident,
+        $State:ident,
         $ChAckReceived:ident,
         $ChAckCompleted:ident$(,)?
     ) => {
         if $Verbose {
             eprintln!("[{}] Starting worker thread", $Id);
         }
-        let (buf_a, buf_b) = Scan::get_buffers::<T>($NIn, DoubleBufferMode::default());
-        let workgroup_barrier = unsafe { WORKGROUP_BARRIER.clone().unwrap() };
-        let workgroup_status_lock = unsafe { WORKGROUP_STATUS.clone().unwrap() };
         loop {
-            let _ = workgroup_barrier.wait();
-            let workgroup_status = *workgroup_status_lock.lock().unwrap();
+            let _ = $State.barrier.wait();
+            let workgroup_status = *$State.status.lock().unwrap();
             if $Verbose {
                 eprintln!("[{}] WorkGroupStatus: {}", $Id, workgroup_status);
             }
@@ -90,18 +133,23 @@ macro_rules! thread_body {
                 );
                 break;
             }
-            WorkGroup::<N> {
-                $Verbose,
-                $Id,
-                $NIn,
-                offset,
-                mode,
-            }
-            .process::<T>(buf_a, buf_b);
+            let task = $State.task.lock().unwrap().as_ref().map(|task| task.0);
+            let result = match task {
+                // NOTE: Safe, since the task outlives the scan in progress, and the scan only
+                // completes once every worker acknowledged the end of its work.
+                Some(task) => panic::catch_unwind(panic::AssertUnwindSafe(|| unsafe {
+                    (*task)($Id, offset, mode)
+                }))
+                .or(Err(format!(
+                    "Worker thread {} panicked in phase {}",
+                    $Id, offset
+                ))),
+                None => Err(format!("Worker thread {} found no task", $Id)),
+            };
             if $Verbose {
                 eprintln!("[{}] Completed work for phase {}", $Id, offset);
             }
-            if $ChAckCompleted.send(()).is_err() {
+            if $ChAckCompleted.send(result).is_err() {
                 eprintln!(
                     "[{}] Failed to signal to main thread end of work phase",
                     $Id
@@ -116,122 +164,256 @@ macro_rules! thread_body {
 }
 
 impl Scan {
-    fn get_buffers<'a, T>(n: usize, mode: DoubleBufferMode) -> (&'a mut [T], &'a mut [T]) {
-        let buf_a_u8 =
-            align::<u8, u64>(n, unsafe { BUF_A[..(2 * n * size_of::<T>())].as_mut_ptr() });
-        let buf_b_u8 =
-            align::<u8, u64>(n, unsafe { BUF_B[..(2 * n * size_of::<T>())].as_mut_ptr() });
-        let buf_a = unsafe { slice::from_raw_parts_mut(buf_a_u8.as_mut_ptr() as *mut T, n) };
-        let buf_b = unsafe { slice::from_raw_parts_mut(buf_b_u8.as_mut_ptr() as *mut T, n) };
-        match mode {
-            DoubleBufferMode::A => (&mut buf_a[..n], &mut buf_b[..n]),
-            DoubleBufferMode::B => (&mut buf_b[..n], &mut buf_a[..n]),
+    /// Create a scan object owning a pool of `num_threads` worker threads.
+    pub fn with_num_threads(verbose: bool, num_threads: usize) -> Self {
+        Self {
+            verbose,
+            pool: Mutex::new(WorkerPool::new(verbose, num_threads)),
         }
     }
 
-    /// Implement the parallel CPU exclusive scan algorithm
-    pub fn process<T, const N: usize>(
+    pub fn get_num_threads(&self) -> usize {
+        self.pool.lock().map_or(0, |pool| pool.get_num_workers())
+    }
+
+    /// Implement the parallel CPU exclusive/inclusive scan algorithm
+    pub fn process<T, O, const N: usize>(
         &self,
-        _def: T,
+        kind: ScanKind,
         v_in: &[T],
         v_out: &mut [T],
     ) -> Result<(), String>
     where
-        T: Copy + IAdd + IDisplay + Send,
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
     {
         let n_in = v_in.len();
         let n_out = v_out.len();
-        let n_buf = BUFFER_LENGTH / size_of::<T>();
         let n_chunks = usize::div_ceil(n_out, N);
-        let d_end = (n_out as f32).log2().ceil() as usize;
+        let d_end = n_out.next_power_of_two().trailing_zeros() as usize;
         Self::check_args(n_in, n_out)?;
-        if n_out > n_buf {
-            return Err(format!(
-                "Expected internal buffer length ({}) to be at least as large as input/output size ({})",
-                n_buf, n_out,
-            ));
+        if N == 0 {
+            return Err("Expected non-zero number of elements per worker thread".to_string());
         }
-        let mut mode = DoubleBufferMode::default();
-        let (buf_a, buf_b) = Scan::get_buffers::<T>(n_out, mode);
-        copy(&v_in[..(n_out - 1)], &mut buf_a[1..n_out])?;
+        // NOTE: Return before acquiring the worker pool, which a failed scan would leave poisoned
+        if n_out == 0 {
+            return Ok(());
+        }
+        let mut pool = self
+            .pool
+            .lock()
+            .or(Err("Failed to acquire worker pool".to_string()))?;
+        let n_workers = pool.get_num_workers();
+        if n_workers == 0 {
+            return Err("Expected non-zero number of worker threads".to_string());
+        }
+        let (buf_a, _backing_store_a) = alloc_aligned::<T, u64>(n_out, O::identity());
+        let (buf_b, _backing_store_b) = alloc_aligned::<T, u64>(n_out, O::identity());
+        copy_scan_input(kind, O::identity(), v_in, buf_a)?;
         copy(buf_a, buf_b)?;
+        let mut buffers = DoubleBuffer::new(buf_a, buf_b);
+        let verbose = self.verbose;
+        let buffers_ref = &buffers;
+        let task = move |id: usize, offset: usize, mode: DoubleBufferMode| {
+            for j in (id..n_chunks).step_by(n_workers) {
+                WorkGroup::<N> {
+                    verbose,
+                    id: j,
+                    n: n_out,
+                    offset,
+                    mode,
+                }
+                .process::<T, O>(buffers_ref);
+            }
+        };
+        let mode = pool.run(&task, d_end)?;
+        copy(buffers.get_result(mode), v_out)?;
+        Ok(())
+    }
+
+    /// Implement the segmented parallel CPU scan algorithm via the flagged operator transformation,
+    /// where the scan restarts at every element whose head flag in `flags` is set
+    pub fn process_segmented<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        flags: &[bool],
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        Self::check_args(n_in, n_out)?;
+        Self::check_flags(n_in, flags.len())?;
+        let mut v_in_seg = alloc(n_in, OpSegmented::<O>::identity());
+        let mut v_out_seg = alloc(n_out, OpSegmented::<O>::identity());
+        segment(kind, O::identity(), flags, v_in, &mut v_in_seg)?;
+        self.process::<Flagged<T>, OpSegmented<O>, N>(
+            ScanKind::Inclusive,
+            &v_in_seg,
+            &mut v_out_seg,
+        )?;
+        unsegment(&v_out_seg, v_out)
+    }
+}
+
+impl IScan for Scan {
+    fn new(verbose: bool) -> Self {
+        let num_threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_num_threads(verbose, num_threads)
+    }
+}
+
+impl WorkerPool {
+    pub fn new(verbose: bool, num_workers: usize) -> Self {
+        let state = Arc::new(WorkerPoolState {
+            barrier: Barrier::new(num_workers + 1),
+            status: Mutex::new(WorkStatus::NoWorkPresent),
+            task: Mutex::new(None),
+        });
         let (ch_ack_received_send, ch_ack_received_recv) = channel::<()>();
-        let (ch_ack_completed_send, ch_ack_completed_recv) = channel::<()>();
-        unsafe {
-            WORKGROUP_BARRIER = Some(Arc::new(Barrier::new(n_chunks + 1)));
-            WORKGROUP_STATUS = Some(Arc::new(Mutex::new(WorkStatus::NoWorkPresent)));
-        }
-        let workgroup_barrier = unsafe { WORKGROUP_BARRIER.clone().unwrap() };
-        let workgroup_status = unsafe { WORKGROUP_STATUS.clone().unwrap() };
-        let worker_pool: Vec<thread::JoinHandle<()>> = (0..n_chunks)
-            .map(|j| {
-                let verbose = self.verbose;
-                let id = j;
-                let n = n_out;
+        let (ch_ack_completed_send, ch_ack_completed_recv) = channel::<Result<(), String>>();
+        let workers = (0..num_workers)
+            .map(|id| {
+                let state = state.clone();
                 let ch_ack_received = ch_ack_received_send.clone();
                 let ch_ack_completed = ch_ack_completed_send.clone();
                 thread::spawn(move || {
-                    thread_body!(verbose, id, n, ch_ack_received, ch_ack_completed);
+                    thread_body!(verbose, id, state, ch_ack_received, ch_ack_completed);
                 })
             })
             .collect();
+        Self {
+            verbose,
+            state,
+            workers,
+            ch_ack_received: ch_ack_received_recv,
+            ch_ack_completed: ch_ack_completed_recv,
+        }
+    }
+
+    pub fn get_num_workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Run `task` on every worker thread for the depths `[0..d_end)`, swapping the double buffer
+    /// mode after each depth, and return the mode of the last depth.
+    pub fn run(&mut self, task: &Task, d_end: usize) -> Result<DoubleBufferMode, String> {
+        let n_workers = self.get_num_workers();
+        // NOTE: Erase the lifetime of the task, which is cleared before returning.
+        let task_ptr = unsafe { mem::transmute::<*const Task, *const Task<'static>>(task) };
+        *self.state.task.lock().unwrap() = Some(TaskPtr(task_ptr));
+        let mut mode = DoubleBufferMode::default();
+        let mut result = Ok(());
         for d in 0..d_end {
             if self.verbose {
                 eprintln!("[_] Depth {}:", d);
             }
             let offset = 1 << d; // 2^d
-            *workgroup_status.lock().unwrap() = WorkStatus::WorkPresent { offset, mode };
-            workgroup_barrier.wait();
+            *self.state.status.lock().unwrap() = WorkStatus::WorkPresent { offset, mode };
+            self.state.barrier.wait();
             if self.verbose {
                 eprintln!("[_] Awaiting acknowledgements of work");
             }
-            if (0..n_chunks).any(|_| ch_ack_received_recv.recv().is_err()) {
-                return Err(format!("Failed work received phase for depth {}", d));
+            if (0..n_workers).any(|_| self.ch_ack_received.recv().is_err()) {
+                result = Err(format!("Failed work received phase for depth {}", d));
+                break;
             }
-            *workgroup_status.lock().unwrap() = WorkStatus::NoWorkPresent;
+            *self.state.status.lock().unwrap() = WorkStatus::NoWorkPresent;
             if self.verbose {
                 eprintln!("[_] Awaiting acknowledgements work has ended");
             }
-            if (0..n_chunks).any(|_| ch_ack_completed_recv.recv().is_err()) {
-                return Err(format!("Failed work completed phase for depth {}", d));
+            for _ in 0..n_workers {
+                match self.ch_ack_completed.recv() {
+                    Ok(Ok(())) => (),
+                    Ok(Err(m)) => result = Err(m),
+                    Err(_) => result = Err(format!("Failed work completed phase for depth {}", d)),
+                }
+            }
+            if result.is_err() {
+                break;
             }
             mode.swap();
         }
+        *self.state.task.lock().unwrap() = None;
+        result.map(|_| mode)
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
         if self.verbose {
             eprintln!("[_] Shutting down threads");
         }
-        *workgroup_status.lock().unwrap() = WorkStatus::Shutdown;
-        workgroup_barrier.wait();
-        worker_pool.into_iter().enumerate().for_each(|(i, j)| {
+        *self.state.status.lock().unwrap() = WorkStatus::Shutdown;
+        self.state.barrier.wait();
+        self.workers.drain(..).enumerate().for_each(|(i, j)| {
             if j.join().is_err() {
                 eprintln!("[_] Failed to join thread {}", i);
             }
         });
-        match mode {
-            DoubleBufferMode::A => copy(buf_a, v_out)?,
-            DoubleBufferMode::B => copy(buf_b, v_out)?,
-        }
-        Ok(())
     }
 }
 
-impl IScan for Scan {
-    fn new(verbose: bool) -> Self {
-        Self { verbose }
+impl<'a, T> DoubleBuffer<'a, T> {
+    pub fn new(buf_a: &'a mut [T], buf_b: &'a mut [T]) -> Self {
+        Self {
+            n: cmp::min(buf_a.len(), buf_b.len()),
+            buf_a: buf_a.as_mut_ptr(),
+            buf_b: buf_b.as_mut_ptr(),
+            _buffers: PhantomData,
+        }
+    }
+
+    /// Returns the source buffer and the range `[k_begin..k_end)` of the destination buffer
+    /// for the double buffer `mode`.
+    ///
+    /// # Safety
+    ///
+    /// No other thread may write to the source buffer, nor access the returned range of the
+    /// destination buffer, for the lifetime of the returned slices.
+    unsafe fn get_chunk(
+        &self,
+        mode: DoubleBufferMode,
+        k_begin: usize,
+        k_end: usize,
+    ) -> (&[T], &mut [T]) {
+        let (buf_src, buf_dst) = match mode {
+            DoubleBufferMode::A => (self.buf_a, self.buf_b),
+            DoubleBufferMode::B => (self.buf_b, self.buf_a),
+        };
+        let k_end_clamp = cmp::min(self.n, k_end);
+        let k_begin_clamp = cmp::min(k_begin, k_end_clamp);
+        (
+            slice::from_raw_parts(buf_src, self.n),
+            slice::from_raw_parts_mut(buf_dst.add(k_begin_clamp), k_end_clamp - k_begin_clamp),
+        )
+    }
+
+    /// Returns the buffer holding the result of the last depth for the double buffer `mode`.
+    pub fn get_result(&mut self, mode: DoubleBufferMode) -> &[T] {
+        let buf = match mode {
+            DoubleBufferMode::A => self.buf_a,
+            DoubleBufferMode::B => self.buf_b,
+        };
+        unsafe { slice::from_raw_parts(buf, self.n) }
     }
 }
 
 impl<const N: usize> WorkGroup<N> {
-    pub fn process<T>(&self, buf_a: &mut [T], buf_b: &mut [T]) -> ()
+    pub fn process<T, O>(&self, buffers: &DoubleBuffer<T>) -> ()
     where
-        T: Copy + IAdd + IDisplay + Send,
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
     {
         let k_begin = self.id * N;
         let k_end_clamp = cmp::min(self.n, k_begin + N);
-        let (buf_a, buf_b) = match self.mode {
-            DoubleBufferMode::A => (&buf_a[..k_end_clamp], &mut buf_b[..k_end_clamp]),
-            DoubleBufferMode::B => (&buf_b[..k_end_clamp], &mut buf_a[..k_end_clamp]),
-        };
+        // NOTE: Safe, since the chunks of the workers are disjoint, and the source buffer is
+        // only written to in the next depth (after every worker acknowledged completion).
+        let (buf_a, buf_b) = unsafe { buffers.get_chunk(self.mode, k_begin, k_end_clamp) };
         for k in k_begin..k_end_clamp {
             if k >= self.offset {
                 let j = k - self.offset;
@@ -240,13 +422,13 @@ impl<const N: usize> WorkGroup<N> {
                 if self.verbose {
                     eprintln!("[{}] *   ({},{},{}): {} + {}", self.id, k, j, k, a, b);
                 }
-                buf_b[k] = a + b;
+                buf_b[k - k_begin] = O::combine(a, b);
             } else {
                 let a = buf_a[k];
                 if self.verbose {
                     eprintln!("[{}] *   ({},{}): {}", self.id, k, k, a);
                 }
-                buf_b[k] = a;
+                buf_b[k - k_begin] = a;
             }
         }
     }
@@ -3,33 +3,126 @@
 
 #[cfg(test)]
 mod tests {
+    use support::OpAdd;
+    use support::ScanKind;
+    use test_scan::gen_ramp_in;
+    use test_scan::gen_ramp_out;
+    use test_scan::golden::get_in;
+    use test_scan::golden::get_out;
+    use test_scan::golden::N100_1;
+    use test_scan::golden::N100_1_INCL;
+    use test_scan::golden::N15_1;
+    use test_scan::golden::N15_1_INCL;
+    use test_scan::golden_tests;
+    use test_scan::prop::test_prop_fold;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
     use test_scan::test_body;
-    use test_scan::N100_1_IN;
-    use test_scan::N100_1_OUT;
-    use test_scan::N15_1_IN;
-    use test_scan::N15_1_OUT;
+    use test_scan::test_body_repeat;
     use test_scan::N16_1_IN;
-    use test_scan::N16_1_OUT;
-    use test_scan::N8_1_IN;
-    use test_scan::N8_1_OUT;
+    use test_scan::N16_1_SEG_HEADS;
+    use test_scan::N16_1_SEG_INCL_OUT;
+    use test_scan::N16_1_SEG_OUT;
+
+    scan_fns!(par_cpu, par_cpu_naive_dbl::Scan; scan, scan_threads, scan_segmented);
+
+    golden_tests!("ScanParCPUNaive2x", scan, 4);
+
+    prop_tests!("ScanParCPUNaive2x", scan, 4);
+
+    #[test]
+    fn test_par_cpu_naive_2x_segmented_n16_1() {
+        test_body(
+            "ScanParCPUNaive2x",
+            scan_segmented::<_, OpAdd, 4>(ScanKind::Exclusive, &N16_1_SEG_HEADS),
+            &N16_1_IN,
+            &N16_1_SEG_OUT,
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_naive_2x_segmented_inclusive_n16_1() {
+        test_body(
+            "ScanParCPUNaive2x",
+            scan_segmented::<_, OpAdd, 4>(ScanKind::Inclusive, &N16_1_SEG_HEADS),
+            &N16_1_IN,
+            &N16_1_SEG_INCL_OUT,
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_naive_2x_n6000_ramp() {
+        test_body(
+            "ScanParCPUNaive2x",
+            scan::<_, OpAdd, 4>(ScanKind::Exclusive),
+            &gen_ramp_in(6000),
+            &gen_ramp_out(6000),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_naive_2x_repeat_3_n100_1() {
+        test_body_repeat(
+            "ScanParCPUNaive2x",
+            3,
+            scan::<_, OpAdd, 4>(ScanKind::Exclusive),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_naive_2x_threads_2_repeat_3_inclusive_n15_1() {
+        test_body_repeat(
+            "ScanParCPUNaive2x",
+            3,
+            scan_threads::<_, OpAdd, 4>(ScanKind::Inclusive, 2),
+            &get_in::<i64>(N15_1),
+            &get_out::<i64>(N15_1_INCL),
+        );
+    }
 
     #[test]
-    fn test_par_cpu_naive_2x_n8_1() {
-        test_body(4, "ScanParCPUNaive2x", &N8_1_IN, &N8_1_OUT);
+    fn test_par_cpu_naive_2x_lanes_1_n100_1() {
+        test_body(
+            "ScanParCPUNaive2x",
+            scan::<_, OpAdd, 1>(ScanKind::Exclusive),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
     }
 
     #[test]
-    fn test_par_cpu_naive_2x_n15_1() {
-        test_body(4, "ScanParCPUNaive2x", &N15_1_IN, &N15_1_OUT);
+    fn test_par_cpu_naive_2x_lanes_64_inclusive_n100_1() {
+        test_body(
+            "ScanParCPUNaive2x",
+            scan::<_, OpAdd, 64>(ScanKind::Inclusive),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1_INCL),
+        );
     }
 
     #[test]
-    fn test_par_cpu_naive_2x_n16_1() {
-        test_body(4, "ScanParCPUNaive2x", &N16_1_IN, &N16_1_OUT);
+    fn test_par_cpu_naive_2x_lanes_8_prop_fold() {
+        test_prop_fold::<i64, OpAdd, _, _>(
+            "ScanParCPUNaive2x",
+            8,
+            scan::<i64, OpAdd, 8>(ScanKind::Exclusive),
+            scan::<i64, OpAdd, 8>(ScanKind::Inclusive),
+        );
     }
 
     #[test]
-    fn test_par_cpu_naive_2x_n100_1() {
-        test_body(4, "ScanParCPUNaive2x", &N100_1_IN, &N100_1_OUT);
+    fn test_par_cpu_naive_2x_n0_reuse() {
+        // NOTE: Reuse the worker pool of a single scan object after an empty input
+        let scan_obj = par_cpu_naive_dbl::Scan::with_num_threads(false, 3);
+        let result = scan_obj.process::<i64, OpAdd, 4>(ScanKind::Exclusive, &[], &mut []);
+        assert_eq!(result, Ok(()));
+        test_body(
+            "ScanParCPUNaive2x",
+            |v_in, v_out| scan_obj.process::<i64, OpAdd, 4>(ScanKind::Exclusive, v_in, v_out),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
     }
 }
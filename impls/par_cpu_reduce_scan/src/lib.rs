@@ -0,0 +1,162 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#![allow(clippy::unused_unit)]
+
+use std::marker::Send;
+use std::marker::Sync;
+use std::thread;
+
+use support::alloc;
+use support::IDisplay;
+use support::IOperator;
+use support::IScan;
+use support::ScanKind;
+
+#[derive(Clone, Copy)]
+pub struct Scan {
+    verbose: bool,
+    num_threads: usize,
+}
+
+#[derive(Copy, Clone)]
+pub struct WorkGroup {
+    verbose: bool,
+    id: usize,
+}
+
+impl Scan {
+    /// Create a scan object running on a fixed number of worker threads, regardless of the
+    /// input length.
+    pub fn with_num_threads(verbose: bool, num_threads: usize) -> Self {
+        Self {
+            verbose,
+            num_threads,
+        }
+    }
+
+    pub fn get_num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Implement the three-phase (reduce-then-scan) parallel CPU exclusive/inclusive scan algorithm:
+    /// 1.  Each worker thread reduces its chunk of the input to a chunk total.
+    /// 2.  The chunk totals are scanned (exclusive) to obtain the chunk offsets.
+    /// 3.  Each worker thread rescans its chunk of the input seeded with its chunk offset.
+    ///
+    /// Chunks are rounded up to a multiple of `N` elements.
+    pub fn process<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IDisplay + Send + Sync,
+        O: IOperator<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        Self::check_args(n_in, n_out)?;
+        if N == 0 {
+            return Err("Expected non-zero chunk granularity".to_string());
+        }
+        if self.num_threads == 0 {
+            return Err("Expected non-zero number of worker threads".to_string());
+        }
+        // NOTE: Chunks hold at least `N` elements, such that an empty input has no chunks
+        let n_chunk = usize::div_ceil(usize::div_ceil(n_out, self.num_threads), N).max(1) * N;
+        let n_chunks = usize::div_ceil(n_out, n_chunk);
+        if self.verbose {
+            eprintln!(
+                "[_] Scanning {} chunks of size {} with {} worker threads",
+                n_chunks, n_chunk, self.num_threads
+            );
+            eprintln!("[_] Reduce:");
+        }
+        let mut totals = alloc(n_chunks, O::identity());
+        thread::scope(|s| {
+            for (id, (chunk_in, total)) in v_in.chunks(n_chunk).zip(totals.iter_mut()).enumerate() {
+                let workgroup = WorkGroup {
+                    verbose: self.verbose,
+                    id,
+                };
+                s.spawn(move || *total = workgroup.reduce::<T, O>(chunk_in));
+            }
+        });
+        if self.verbose {
+            eprintln!("[_] Totals: {:?}", totals);
+        }
+        let mut acc = O::identity();
+        for total in totals.iter_mut() {
+            let next = O::combine(acc, *total);
+            *total = acc;
+            acc = next;
+        }
+        if self.verbose {
+            eprintln!("[_] Offsets: {:?}", totals);
+            eprintln!("[_] Scan:");
+        }
+        thread::scope(|s| {
+            for (id, ((chunk_in, chunk_out), &offset)) in v_in
+                .chunks(n_chunk)
+                .zip(v_out.chunks_mut(n_chunk))
+                .zip(totals.iter())
+                .enumerate()
+            {
+                let workgroup = WorkGroup {
+                    verbose: self.verbose,
+                    id,
+                };
+                s.spawn(move || workgroup.scan::<T, O>(kind, offset, chunk_in, chunk_out));
+            }
+        });
+        Ok(())
+    }
+}
+
+impl IScan for Scan {
+    fn new(verbose: bool) -> Self {
+        let num_threads = thread::available_parallelism().map_or(1, |n| n.get());
+        Self::with_num_threads(verbose, num_threads)
+    }
+}
+
+impl WorkGroup {
+    pub fn reduce<T, O>(&self, chunk_in: &[T]) -> T
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        let total = chunk_in
+            .iter()
+            .fold(O::identity(), |acc, &x| O::combine(acc, x));
+        if self.verbose {
+            eprintln!("[{}] *   total {}", self.id, total);
+        }
+        total
+    }
+
+    pub fn scan<T, O>(&self, kind: ScanKind, offset: T, chunk_in: &[T], chunk_out: &mut [T]) -> ()
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        if self.verbose {
+            eprintln!("[{}] *   offset {}", self.id, offset);
+        }
+        let mut acc = offset;
+        for (y, &x) in chunk_out.iter_mut().zip(chunk_in.iter()) {
+            match kind {
+                ScanKind::Exclusive => {
+                    *y = acc;
+                    acc = O::combine(acc, x);
+                }
+                ScanKind::Inclusive => {
+                    acc = O::combine(acc, x);
+                    *y = acc;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#[cfg(test)]
+mod tests {
+    use support::compensate;
+    use support::uncompensate;
+    use support::Compensated;
+    use support::OpAdd;
+    use support::OpAddCompensated;
+    use support::ScanKind;
+    use test_scan::gen_sensor_in;
+    use test_scan::gen_sensor_ref;
+    use test_scan::get_max_rel_error;
+    use test_scan::golden::get_in;
+    use test_scan::golden::get_out;
+    use test_scan::golden::N100_1;
+    use test_scan::golden::N15_1;
+    use test_scan::golden::N15_1_INCL;
+    use test_scan::golden_tests;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
+    use test_scan::test_body;
+
+    scan_fns!(par_cpu, par_cpu_reduce_scan::Scan; scan, scan_threads);
+
+    golden_tests!("ScanParCPUReduceScan", scan, 4);
+
+    prop_tests!("ScanParCPUReduceScan", scan, 4);
+
+    #[test]
+    fn test_par_cpu_reduce_scan_threads_3_n100_1() {
+        test_body(
+            "ScanParCPUReduceScan",
+            scan_threads::<_, OpAdd, 4>(ScanKind::Exclusive, 3),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_reduce_scan_threads_32_inclusive_n15_1() {
+        test_body(
+            "ScanParCPUReduceScan",
+            scan_threads::<_, OpAdd, 4>(ScanKind::Inclusive, 32),
+            &get_in::<i64>(N15_1),
+            &get_out::<i64>(N15_1_INCL),
+        );
+    }
+
+    #[test]
+    fn test_par_cpu_reduce_scan_n0() {
+        let scan_obj = par_cpu_reduce_scan::Scan::with_num_threads(false, 3);
+        let result = scan_obj.process::<i64, OpAdd, 4>(ScanKind::Exclusive, &[], &mut []);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn test_par_cpu_reduce_scan_accuracy_compensated_f32_n1048576_sensor() {
+        let n = 1 << 20;
+        let v_in = gen_sensor_in(n);
+        let v_ref = gen_sensor_ref(&v_in);
+        let mut v_in_comp = vec![Compensated::default(); n];
+        let mut v_out_comp = vec![Compensated::default(); n];
+        let mut v_out = vec![0.0; n];
+        assert_eq!(compensate(&v_in, &mut v_in_comp), Ok(()));
+        let scan_obj = par_cpu_reduce_scan::Scan::with_num_threads(false, 7);
+        let result = scan_obj.process::<Compensated<f32>, OpAddCompensated, 4>(
+            ScanKind::Inclusive,
+            &v_in_comp,
+            &mut v_out_comp,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(uncompensate(&v_out_comp, &mut v_out), Ok(()));
+        assert!(get_max_rel_error(&v_out, &v_ref) <= f32::EPSILON as f64);
+    }
+}
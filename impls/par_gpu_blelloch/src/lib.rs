@@ -0,0 +1,542 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#![allow(non_snake_case)]
+
+use std::cmp;
+use std::num::NonZero;
+use std::sync::Mutex;
+
+use bytemuck::bytes_of;
+use bytemuck::cast_slice;
+use bytemuck::Pod;
+use env_logger::try_init as try_init_logger;
+use pollster::block_on;
+use support::copy_casted;
+use support::IBottom;
+use support::ICast;
+use support::IDisplay;
+use support::IOperator;
+use support::IScan;
+use support::IZero;
+use support::ScanKind;
+use wgpu::include_wgsl;
+use wgpu::BindGroup;
+use wgpu::BindGroupDescriptor;
+use wgpu::BindGroupEntry;
+use wgpu::BindGroupLayout;
+use wgpu::BindGroupLayoutDescriptor;
+use wgpu::BindGroupLayoutEntry;
+use wgpu::BindingResource;
+use wgpu::BindingType;
+use wgpu::Buffer;
+use wgpu::BufferBinding;
+use wgpu::BufferBindingType;
+use wgpu::BufferDescriptor;
+use wgpu::BufferUsages;
+use wgpu::CommandEncoderDescriptor;
+use wgpu::ComputePassDescriptor;
+use wgpu::ComputePipeline;
+use wgpu::ComputePipelineDescriptor;
+use wgpu::Device;
+use wgpu::DeviceDescriptor;
+use wgpu::DownlevelFlags;
+use wgpu::Instance;
+use wgpu::InstanceDescriptor;
+use wgpu::Limits;
+use wgpu::Maintain;
+use wgpu::MapMode;
+use wgpu::MemoryHints;
+use wgpu::PipelineCompilationOptions;
+use wgpu::PipelineLayoutDescriptor;
+use wgpu::Queue;
+use wgpu::RequestAdapterOptions;
+use wgpu::ShaderStages;
+
+/// Number of elements scanned in workgroup memory by a single workgroup (see `shader.wgsl`).
+pub const TILE_SIZE: usize = 512;
+
+/// Max number of dispatches of a scan, i.e., two per level for at most `log2(n)` levels for any
+/// `n: usize`.
+const MAX_PASSES: usize = 2 * usize::BITS as usize;
+
+pub struct Scan {
+    verbose: bool,
+    context: Mutex<Option<GpuContext>>,
+}
+
+/// The GPU state created once (on first use) and reused by every scan of the `Scan` object.
+/// The storage and readback buffers are only reallocated when a larger input arrives.
+pub struct GpuContext {
+    verbose: bool,
+    device: Device,
+    queue: Queue,
+    bind_group_layout: BindGroupLayout,
+    pipeline_scan_tiles: ComputePipeline,
+    pipeline_add_sums: ComputePipeline,
+    params_align: usize,
+    params_buffer: Buffer,
+    op_buffer: Buffer,
+    identity_buffer: Buffer,
+    is_signed_buffer: Buffer,
+    buffers: Option<GpuBuffers>,
+}
+
+/// The buffers sized for hierarchies of up to `capacity` elements, and their bind group.
+pub struct GpuBuffers {
+    capacity: usize,
+    data_buffer: Buffer,
+    download_buffer: Buffer,
+    bind_group: BindGroup,
+}
+
+/// A compute pass over a level of the hierarchy, named after its entry point in `shader.wgsl`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pass {
+    ScanTiles,
+    AddSums,
+}
+
+/// A level of the hierarchy of tile sums, stored in `buf[offset..(offset + n)]`.
+#[derive(Clone, Copy, Debug)]
+pub struct Level {
+    n: usize,
+    offset: usize,
+}
+
+impl GpuContext {
+    pub fn new(verbose: bool) -> Result<Self, String> {
+        // NOTE: Only the first call initializes the logger
+        let _ = try_init_logger();
+
+        let instance = Instance::new(&InstanceDescriptor::default());
+        let adapter = block_on(instance.request_adapter(&RequestAdapterOptions::default()))
+            .ok_or("Failed to request adapter".to_string())?;
+
+        if verbose {
+            eprintln!("Found adapter: {:#?}", adapter.get_info());
+        }
+
+        if !adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(DownlevelFlags::COMPUTE_SHADERS)
+        {
+            return Err("Adapter does not support compute shaders".to_string());
+        }
+
+        let (device, queue) = block_on(adapter.request_device(
+            &DeviceDescriptor {
+                label: None,
+                required_features: wgpu::Features::empty(),
+                required_limits: Limits::downlevel_defaults(),
+                memory_hints: MemoryHints::MemoryUsage,
+            },
+            None,
+        ))
+        .or(Err("Failed to request adapter".to_string()))?;
+
+        let params_align = device.limits().min_uniform_buffer_offset_alignment as usize;
+        let module = device.create_shader_module(include_wgsl!("shader.wgsl"));
+        let uniform_usage = BufferUsages::UNIFORM | BufferUsages::COPY_DST;
+        let params_buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: (MAX_PASSES * params_align) as u64,
+            usage: uniform_usage,
+            mapped_at_creation: false,
+        });
+        let op_buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: size_of::<u32>() as u64,
+            usage: uniform_usage,
+            mapped_at_creation: false,
+        });
+        let identity_buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: size_of::<i64>() as u64,
+            usage: uniform_usage,
+            mapped_at_creation: false,
+        });
+        let is_signed_buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: size_of::<u32>() as u64,
+            usage: uniform_usage,
+            mapped_at_creation: false,
+        });
+
+        let input_entry_params = BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                min_binding_size: Some(NonZero::new(4 * size_of::<u32>() as u64).unwrap()),
+                has_dynamic_offset: true,
+            },
+            count: None,
+        };
+        let input_entry_op = BindGroupLayoutEntry {
+            binding: 1,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                min_binding_size: Some(NonZero::new(size_of::<u32>() as u64).unwrap()),
+                has_dynamic_offset: false,
+            },
+            count: None,
+        };
+        let input_entry_identity = BindGroupLayoutEntry {
+            binding: 2,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                min_binding_size: Some(NonZero::new(size_of::<i64>() as u64).unwrap()),
+                has_dynamic_offset: false,
+            },
+            count: None,
+        };
+        let entry_data = BindGroupLayoutEntry {
+            binding: 3,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: false },
+                min_binding_size: Some(NonZero::new(size_of::<i64>() as u64).unwrap()),
+                has_dynamic_offset: false,
+            },
+            count: None,
+        };
+        let input_entry_is_signed = BindGroupLayoutEntry {
+            binding: 4,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                min_binding_size: Some(NonZero::new(size_of::<u32>() as u64).unwrap()),
+                has_dynamic_offset: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[
+                input_entry_params,
+                input_entry_op,
+                input_entry_identity,
+                entry_data,
+                input_entry_is_signed,
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: None,
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline_scan_tiles = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: None,
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("scan_tiles"),
+            compilation_options: PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        let pipeline_add_sums = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: None,
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("add_sums"),
+            compilation_options: PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        Ok(Self {
+            verbose,
+            device,
+            queue,
+            bind_group_layout,
+            pipeline_scan_tiles,
+            pipeline_add_sums,
+            params_align,
+            params_buffer,
+            op_buffer,
+            identity_buffer,
+            is_signed_buffer,
+            buffers: None,
+        })
+    }
+
+    /// Get the max size (in bytes) of a storage buffer supported by the device.
+    fn get_max_buf_size(&self) -> usize {
+        let limits = self.device.limits();
+        cmp::min(
+            limits.max_storage_buffer_binding_size as u64,
+            limits.max_buffer_size,
+        ) as usize
+    }
+
+    /// Ensure the buffers fit a hierarchy of `n_buf` elements, reallocating them if `n_buf`
+    /// exceeds their capacity.
+    fn reserve(&mut self, n_buf: usize) {
+        let max_buf_size = self.get_max_buf_size();
+        let capacity = self.buffers.as_ref().map_or(0, |buffers| buffers.capacity);
+        if n_buf > capacity {
+            let capacity = cmp::min(n_buf.next_power_of_two(), max_buf_size / size_of::<i64>());
+            if self.verbose {
+                eprintln!("Growing GPU buffers to capacity {}", capacity);
+            }
+            self.buffers = Some(self.create_buffers(capacity));
+        }
+    }
+
+    fn create_buffers(&self, capacity: usize) -> GpuBuffers {
+        let size = (capacity * size_of::<i64>()) as u64;
+        let data_buffer = self.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let download_buffer = self.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let input_bind_group_params = BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer: &self.params_buffer,
+                offset: 0,
+                size: NonZero::new(4 * size_of::<u32>() as u64),
+            }),
+        };
+        let input_bind_group_op = BindGroupEntry {
+            binding: 1,
+            resource: self.op_buffer.as_entire_binding(),
+        };
+        let input_bind_group_identity = BindGroupEntry {
+            binding: 2,
+            resource: self.identity_buffer.as_entire_binding(),
+        };
+        let bind_group_data = BindGroupEntry {
+            binding: 3,
+            resource: data_buffer.as_entire_binding(),
+        };
+        let input_bind_group_is_signed = BindGroupEntry {
+            binding: 4,
+            resource: self.is_signed_buffer.as_entire_binding(),
+        };
+        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
+            label: None,
+            layout: &self.bind_group_layout,
+            entries: &[
+                input_bind_group_params,
+                input_bind_group_op,
+                input_bind_group_identity,
+                bind_group_data,
+                input_bind_group_is_signed,
+            ],
+        });
+
+        GpuBuffers {
+            capacity,
+            data_buffer,
+            download_buffer,
+            bind_group,
+        }
+    }
+}
+
+impl Scan {
+    /// Returns the levels of the hierarchy for an input of length `n`, where the elements of each
+    /// level are the sums of the tiles of the previous level, down to a single element.
+    pub fn get_levels(n: usize) -> Vec<Level> {
+        let mut levels = vec![Level { n, offset: 0 }];
+        loop {
+            let level = levels[levels.len() - 1];
+            if levels.len() > 1 && level.n <= 1 {
+                break;
+            }
+            levels.push(Level {
+                n: usize::div_ceil(level.n, TILE_SIZE),
+                offset: level.offset + level.n,
+            });
+        }
+        levels
+    }
+
+    /// Returns the (2D) dispatch size covering `n_tiles` workgroups.
+    fn get_dispatch_size(n_tiles: usize, max_workgroups: usize) -> (u32, u32) {
+        let n_x = cmp::max(cmp::min(n_tiles, max_workgroups), 1);
+        let n_y = usize::div_ceil(n_tiles, n_x);
+        (n_x as u32, cmp::max(n_y, 1) as u32)
+    }
+
+    /// Implement the parallel GPU work-efficient (Blelloch) exclusive/inclusive scan algorithm,
+    /// scanning tiles in workgroup memory and recursively scanning the sums of the tiles.
+    /// Elements are scanned as 64-bit integers, sign- (resp. zero-) extended from signed
+    /// (resp. unsigned) `T`, so that the truncated results match the CPU implementations.
+    pub fn process<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IBottom + ICast<i64> + IDisplay + IZero + Pod + Send,
+        O: IOperator<T>,
+        i64: ICast<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        let op = O::KIND as u32;
+        let identity: i64 = O::identity().cast();
+        let is_signed = (T::BOTTOM < T::ZERO) as u32;
+
+        if self.verbose {
+            eprintln!("Starting par_gpu_blelloch");
+            eprintln!("v_in: {:?}", v_in);
+            eprintln!("v_out: {:?}", v_out);
+        }
+
+        Self::check_args(n_in, n_out)?;
+        if n_out == 0 {
+            return Ok(());
+        }
+
+        let levels = Self::get_levels(n_out);
+        let n_buf = levels.iter().map(|level| level.n).sum::<usize>();
+        let mut v_buf_gpu: Vec<i64> = vec![identity; n_buf];
+        copy_casted::<T, i64>(v_in, &mut v_buf_gpu[..n_in])?;
+
+        if self.verbose {
+            eprintln!("levels: {:?}", levels);
+        }
+
+        let mut context = self
+            .context
+            .lock()
+            .or(Err("Failed to acquire GPU context".to_string()))?;
+        if context.is_none() {
+            *context = Some(GpuContext::new(self.verbose)?);
+        }
+        let context = context.as_mut().unwrap();
+
+        let max_workgroups = context.device.limits().max_compute_workgroups_per_dimension as usize;
+        let max_buf_size = context.get_max_buf_size();
+        if n_buf * size_of::<i64>() > max_buf_size {
+            return Err(format!(
+                "Expected input length ({}) within the max supported GPU buffer size ({} bytes)",
+                n_out, max_buf_size
+            ));
+        }
+
+        // NOTE: Pack the parameters of every dispatch, aligned for use as dynamic offsets:
+        // first the scan of the tiles of every level (bottom-up), then the addition of the
+        // scanned sums back to the tiles of every level (top-down).
+        let params_align = context.params_align;
+        let n_levels = levels.len() - 1;
+        let passes = (0..n_levels)
+            .map(|l| (l, Pass::ScanTiles))
+            .chain((0..(n_levels - 1)).rev().map(|l| (l, Pass::AddSums)))
+            .collect::<Vec<(usize, Pass)>>();
+        let mut params = vec![0_u8; passes.len() * params_align];
+        for (i, (l, _)) in passes.iter().enumerate() {
+            let level_params: [u32; 4] = [
+                levels[*l].n as u32,
+                levels[*l].offset as u32,
+                levels[*l + 1].offset as u32,
+                0,
+            ];
+            let k = i * params_align;
+            params[k..(k + 16)].copy_from_slice(cast_slice(&level_params));
+        }
+
+        context.reserve(n_buf);
+        let GpuContext {
+            device,
+            queue,
+            pipeline_scan_tiles,
+            pipeline_add_sums,
+            params_buffer,
+            op_buffer,
+            identity_buffer,
+            is_signed_buffer,
+            buffers,
+            ..
+        } = context;
+        let buffers = buffers.as_ref().unwrap();
+
+        queue.write_buffer(params_buffer, 0, &params);
+        queue.write_buffer(op_buffer, 0, bytes_of(&op));
+        queue.write_buffer(identity_buffer, 0, bytes_of(&identity));
+        queue.write_buffer(is_signed_buffer, 0, bytes_of(&is_signed));
+        queue.write_buffer(&buffers.data_buffer, 0, cast_slice(&v_buf_gpu));
+        let download_size = (n_out * size_of::<i64>()) as u64;
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+
+        let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: None,
+            timestamp_writes: None,
+        });
+        for (i, (l, pass)) in passes.iter().enumerate() {
+            let n_tiles = usize::div_ceil(levels[*l].n, TILE_SIZE);
+            let (n_x, n_y) = Self::get_dispatch_size(n_tiles, max_workgroups);
+            if self.verbose {
+                eprintln!(
+                    "Dispatch {} for level {}: {:?} ({}x{} workgroups)",
+                    i, l, pass, n_x, n_y
+                );
+            }
+            match pass {
+                Pass::ScanTiles => compute_pass.set_pipeline(pipeline_scan_tiles),
+                Pass::AddSums => compute_pass.set_pipeline(pipeline_add_sums),
+            }
+            compute_pass.set_bind_group(0, &buffers.bind_group, &[(i * params_align) as u32]);
+            compute_pass.dispatch_workgroups(n_x, n_y, 1);
+        }
+        drop(compute_pass);
+
+        encoder.copy_buffer_to_buffer(
+            &buffers.data_buffer,
+            0,
+            &buffers.download_buffer,
+            0,
+            download_size,
+        );
+
+        let command_buffer = encoder.finish();
+        queue.submit([command_buffer]);
+
+        let buffer_slice = buffers.download_buffer.slice(..download_size);
+        buffer_slice.map_async(MapMode::Read, |_| {});
+
+        let _ = device.poll(Maintain::Wait);
+        let result = {
+            let data = buffer_slice.get_mapped_range();
+            if self.verbose && n_out <= TILE_SIZE {
+                eprintln!("data: {:?}", cast_slice::<u8, i64>(&data));
+            }
+            copy_casted::<i64, T>(cast_slice(&data), v_out)
+        };
+        // NOTE: Unmap the readback buffer before any error is returned, so it can be mapped again
+        buffers.download_buffer.unmap();
+        result?;
+
+        if kind == ScanKind::Inclusive {
+            for k in 0..n_out {
+                v_out[k] = O::combine(v_out[k], v_in[k]);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl IScan for Scan {
+    fn new(verbose: bool) -> Self {
+        Self {
+            verbose,
+            context: Mutex::new(None),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+// NOTE: Every workgroup scans a tile of `TILE_SIZE` elements, two elements per invocation
+const WORKGROUP_SIZE: u32 = 256u;
+const TILE_SIZE: u32 = 512u;
+
+// NOTE: The parameters of a single level, passed per dispatch via a dynamic offset.
+// The elements of the level are `buf[offset_data..(offset_data + n)]`, and the sums of its tiles
+// are `buf[offset_sums..(offset_sums + ceil(n / TILE_SIZE))]`.
+struct Params {
+    n: u32,
+    offset_data: u32,
+    offset_sums: u32,
+    _padding: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<uniform> op: u32;
+@group(0) @binding(2)
+var<uniform> identity: vec2<u32>;
+@group(0) @binding(3)
+var<storage, read_write> buf: array<vec2<u32>>;
+@group(0) @binding(4)
+var<uniform> is_signed: u32;
+
+var<workgroup> tile: array<vec2<u32>, TILE_SIZE>;
+
+// NOTE: Operator codes follow the discriminants of `support::OperatorKind`.
+// Elements are 64-bit integers stored as (lo, hi) pairs of u32 words, and the operators wrap on
+// overflow like those of `support`.
+fn combine(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    switch (op) {
+        case 1u: {
+            return mul64(a, b);
+        }
+        case 2u: {
+            return select(b, a, less64(a, b));
+        }
+        case 3u: {
+            return select(a, b, less64(a, b));
+        }
+        case 4u: {
+            return a & b;
+        }
+        case 5u: {
+            return a | b;
+        }
+        case 6u: {
+            return a ^ b;
+        }
+        default: {
+            return add64(a, b);
+        }
+    }
+}
+
+fn add64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let lo = a.x + b.x;
+    let carry = select(0u, 1u, lo < a.x);
+    return vec2<u32>(lo, a.y + b.y + carry);
+}
+
+// Returns the full 64-bit product of two 32-bit words, from the products of their 16-bit halves.
+fn mul_wide(a: u32, b: u32) -> vec2<u32> {
+    let a_lo = a & 0xFFFFu;
+    let a_hi = a >> 16u;
+    let b_lo = b & 0xFFFFu;
+    let b_hi = b >> 16u;
+    let p_lo = a_lo * b_lo;
+    let p_mid_a = a_lo * b_hi;
+    let p_mid_b = a_hi * b_lo;
+    let p_hi = a_hi * b_hi;
+    let p_mid = p_mid_a + p_mid_b;
+    let carry_mid = select(0u, 0x10000u, p_mid < p_mid_a);
+    let lo = p_lo + (p_mid << 16u);
+    let carry_lo = select(0u, 1u, lo < p_lo);
+    return vec2<u32>(lo, p_hi + (p_mid >> 16u) + carry_mid + carry_lo);
+}
+
+fn mul64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let p = mul_wide(a.x, b.x);
+    return vec2<u32>(p.x, p.y + a.x * b.y + a.y * b.x);
+}
+
+// Compares the elements as signed (resp. unsigned) integers if `is_signed` is set (resp. unset).
+fn less64(a: vec2<u32>, b: vec2<u32>) -> bool {
+    if (a.y != b.y) {
+        if (is_signed != 0u) {
+            return bitcast<i32>(a.y) < bitcast<i32>(b.y);
+        }
+        return a.y < b.y;
+    }
+    return a.x < b.x;
+}
+
+fn get_tile_id(wid: vec3<u32>, num_wgs: vec3<u32>) -> u32 {
+    return wid.y * num_wgs.x + wid.x;
+}
+
+fn load(k: u32) -> vec2<u32> {
+    if (k < params.n) {
+        return buf[params.offset_data + k];
+    }
+    return identity;
+}
+
+fn store(k: u32, x: vec2<u32>) {
+    if (k < params.n) {
+        buf[params.offset_data + k] = x;
+    }
+}
+
+// Scan (exclusive) every tile of the level in workgroup memory, and write the sum of every tile.
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn scan_tiles(
+    @builtin(local_invocation_id) lid: vec3<u32>,
+    @builtin(workgroup_id) wid: vec3<u32>,
+    @builtin(num_workgroups) num_wgs: vec3<u32>,
+) {
+    let t = get_tile_id(wid, num_wgs);
+    let i = lid.x;
+    let k_begin = t * TILE_SIZE;
+    tile[i] = load(k_begin + i);
+    tile[i + WORKGROUP_SIZE] = load(k_begin + i + WORKGROUP_SIZE);
+    var offset = 1u;
+    for (var d = TILE_SIZE >> 1u; d > 0u; d >>= 1u) {
+        workgroupBarrier();
+        if (i < d) {
+            let j = offset * (2u * i + 1u) - 1u;
+            let k = offset * (2u * i + 2u) - 1u;
+            tile[k] = combine(tile[j], tile[k]);
+        }
+        offset <<= 1u;
+    }
+    workgroupBarrier();
+    if (i == 0u) {
+        if (k_begin < params.n) {
+            buf[params.offset_sums + t] = tile[TILE_SIZE - 1u];
+        }
+        tile[TILE_SIZE - 1u] = identity;
+    }
+    for (var d = 1u; d < TILE_SIZE; d <<= 1u) {
+        offset >>= 1u;
+        workgroupBarrier();
+        if (i < d) {
+            let j = offset * (2u * i + 1u) - 1u;
+            let k = offset * (2u * i + 2u) - 1u;
+            let a = tile[k];
+            let b = tile[j];
+            tile[j] = a;
+            tile[k] = combine(a, b);
+        }
+    }
+    workgroupBarrier();
+    store(k_begin + i, tile[i]);
+    store(k_begin + i + WORKGROUP_SIZE, tile[i + WORKGROUP_SIZE]);
+}
+
+// Add the scanned sum of every tile back to the elements of the tile.
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn add_sums(
+    @builtin(local_invocation_id) lid: vec3<u32>,
+    @builtin(workgroup_id) wid: vec3<u32>,
+    @builtin(num_workgroups) num_wgs: vec3<u32>,
+) {
+    let t = get_tile_id(wid, num_wgs);
+    let i = lid.x;
+    let k_begin = t * TILE_SIZE;
+    if (k_begin >= params.n) {
+        return;
+    }
+    let sum = buf[params.offset_sums + t];
+    store(k_begin + i, combine(sum, load(k_begin + i)));
+    store(k_begin + i + WORKGROUP_SIZE, combine(sum, load(k_begin + i + WORKGROUP_SIZE)));
+}
@@ -0,0 +1,54 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#[cfg(test)]
+mod tests {
+    use support::IScan;
+    use support::OpAdd;
+    use support::ScanKind;
+    use test_scan::gen_ramp_in;
+    use test_scan::gen_ramp_out;
+    use test_scan::golden_tests_int;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
+    use test_scan::test_body;
+
+    scan_fns!(par_gpu, par_gpu_blelloch::Scan; scan);
+
+    golden_tests_int!("ScanParGPUBlelloch", scan, 4);
+
+    prop_tests!("ScanParGPUBlelloch", scan, 4);
+
+    #[test]
+    fn test_par_gpu_blelloch_n16384_ramp() {
+        test_body(
+            "ScanParGPUBlelloch",
+            scan::<_, OpAdd, 4>(ScanKind::Exclusive),
+            &gen_ramp_in(16384),
+            &gen_ramp_out(16384),
+        );
+    }
+
+    #[test]
+    fn test_par_gpu_blelloch_n2000000_ramp() {
+        test_body(
+            "ScanParGPUBlelloch",
+            scan::<_, OpAdd, 4>(ScanKind::Exclusive),
+            &gen_ramp_in(2_000_000),
+            &gen_ramp_out(2_000_000),
+        );
+    }
+
+    #[test]
+    fn test_par_gpu_blelloch_reuse_context() {
+        // NOTE: Reuse the GPU context of a single scan object across growing and shrinking inputs
+        let scan_obj = par_gpu_blelloch::Scan::new(false);
+        for n in [100, 5000, 16, 5001] {
+            let v_in = gen_ramp_in(n);
+            let mut v_out = vec![0; n];
+            let result = scan_obj.process::<i64, OpAdd, 4>(ScanKind::Exclusive, &v_in, &mut v_out);
+            assert_eq!(result, Ok(()));
+            assert!(v_out == gen_ramp_out(n));
+        }
+    }
+}
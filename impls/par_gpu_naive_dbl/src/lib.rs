@@ -2,37 +2,48 @@
 // SPDX-License-Identifier: BSD-3-Clause
 
 #![allow(dead_code)]
+#![allow(clippy::unused_unit)]
 #![allow(non_snake_case)]
 #![allow(unused_mut)]
 #![allow(unused_variables)]
 
+use std::cmp;
 use std::num::NonZero;
+use std::sync::Mutex;
 
 use bytemuck::bytes_of;
 use bytemuck::cast_slice;
 use bytemuck::Pod;
-use env_logger::init as init_logger;
+use env_logger::try_init as try_init_logger;
 use pollster::block_on;
 use support::copy_casted;
 use support::DoubleBufferMode;
-use support::IAdd;
+use support::IBottom;
 use support::ICast;
 use support::IDisplay;
+use support::IOperator;
 use support::IScan;
+use support::IZero;
+use support::ScanKind;
 use wgpu::include_wgsl;
-use wgpu::util::BufferInitDescriptor;
-use wgpu::util::DeviceExt;
+use wgpu::BindGroup;
 use wgpu::BindGroupDescriptor;
 use wgpu::BindGroupEntry;
+use wgpu::BindGroupLayout;
 use wgpu::BindGroupLayoutDescriptor;
 use wgpu::BindGroupLayoutEntry;
+use wgpu::BindingResource;
 use wgpu::BindingType;
+use wgpu::Buffer;
+use wgpu::BufferBinding;
 use wgpu::BufferBindingType;
 use wgpu::BufferDescriptor;
 use wgpu::BufferUsages;
 use wgpu::CommandEncoderDescriptor;
 use wgpu::ComputePassDescriptor;
+use wgpu::ComputePipeline;
 use wgpu::ComputePipelineDescriptor;
+use wgpu::Device;
 use wgpu::DeviceDescriptor;
 use wgpu::DownlevelFlags;
 use wgpu::Instance;
@@ -43,61 +54,57 @@ use wgpu::MapMode;
 use wgpu::MemoryHints;
 use wgpu::PipelineCompilationOptions;
 use wgpu::PipelineLayoutDescriptor;
+use wgpu::Queue;
 use wgpu::RequestAdapterOptions;
 use wgpu::ShaderStages;
 
-#[derive(Clone, Copy)]
+/// Number of invocations per workgroup (see `shader.wgsl`).
+const WORKGROUP_SIZE: usize = 64;
+
+/// Max number of depths of a scan, i.e., `ceil(log2(n))` for any `n: usize`.
+const MAX_DEPTHS: usize = usize::BITS as usize;
+
 pub struct Scan {
     verbose: bool,
+    context: Mutex<Option<GpuContext>>,
 }
 
-impl Scan {
-    /// Implement the parallel GPU exclusive scan algorithm
-    pub fn process<T, const N: usize>(
-        &self,
-        def: T,
-        v_in: &[T],
-        v_out: &mut [T],
-    ) -> Result<(), String>
-    where
-        T: Copy + Eq + IAdd + ICast<i32> + IDisplay + Ord + Pod + Send,
-        i32: ICast<T>,
-    {
-        const WORKGROUP_SIZE: usize = 64;
-        let n_in = v_in.len();
-        let n_out = v_out.len();
-        let num_chunks = usize::div_ceil(n_out, WORKGROUP_SIZE);
-        let d_end = (n_out as f32).log2().ceil() as u32;
-        let mode = DoubleBufferMode::default();
-
-        if self.verbose {
-            eprintln!("Starting par_gpu_naive_dbl");
-            eprintln!("v_in: {:?}", v_in);
-            eprintln!("v_out: {:?}", v_out);
-        }
-
-        Self::check_args(n_in, n_out)?;
-        if v_in.iter().max().cloned().unwrap_or(def) > i32::MAX.cast() {
-            return Err(format!(
-                "Values in input larger than max supported GPU value ({}:i32)",
-                i32::MAX
-            ));
-        }
-
-        let mut v_in_gpu: Vec<i32> = vec![def.cast(); n_in];
-        copy_casted::<T, i32>(&v_in[..(n_in - 1)], &mut v_in_gpu[1..n_in])?;
+/// The GPU state created once (on first use) and reused by every scan of the `Scan` object.
+/// The storage and readback buffers are only reallocated when a larger input arrives.
+pub struct GpuContext {
+    verbose: bool,
+    device: Device,
+    queue: Queue,
+    bind_group_layout: BindGroupLayout,
+    pipeline: ComputePipeline,
+    params_align: usize,
+    n_buffer: Buffer,
+    N_buffer: Buffer,
+    params_buffer: Buffer,
+    op_buffer: Buffer,
+    is_signed_buffer: Buffer,
+    buffers: Option<GpuBuffers>,
+}
 
-        if self.verbose {
-            eprintln!("v_in_gpu: {:?}", v_in_gpu);
-        }
+/// The buffers sized for inputs of up to `capacity` elements, and their bind group.
+pub struct GpuBuffers {
+    capacity: usize,
+    input_data_buffer: Buffer,
+    output_data_buffer: Buffer,
+    download_buffer: Buffer,
+    bind_group: BindGroup,
+}
 
-        init_logger();
+impl GpuContext {
+    pub fn new(verbose: bool) -> Result<Self, String> {
+        // NOTE: Only the first call initializes the logger
+        let _ = try_init_logger();
 
         let instance = Instance::new(&InstanceDescriptor::default());
         let adapter = block_on(instance.request_adapter(&RequestAdapterOptions::default()))
             .ok_or("Failed to request adapter".to_string())?;
 
-        if self.verbose {
+        if verbose {
             eprintln!("Found adapter: {:#?}", adapter.get_info());
         }
 
@@ -120,48 +127,37 @@ impl Scan {
         ))
         .or(Err("Failed to request adapter".to_string()))?;
 
+        let params_align = device.limits().min_uniform_buffer_offset_alignment as usize;
         let module = device.create_shader_module(include_wgsl!("shader.wgsl"));
-        let input_n_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: None,
-            contents: bytes_of(&n_out),
-            usage: BufferUsages::UNIFORM,
-        });
-        let input_N_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: None,
-            contents: bytes_of(&N),
-            usage: BufferUsages::UNIFORM,
-        });
-        let input_d_end_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        let uniform_usage = BufferUsages::UNIFORM | BufferUsages::COPY_DST;
+        let n_buffer = device.create_buffer(&BufferDescriptor {
             label: None,
-            contents: bytes_of(&d_end),
-            usage: BufferUsages::UNIFORM,
-        });
-        let input_mode_buffer = device.create_buffer_init(&BufferInitDescriptor {
-            label: None,
-            contents: bytes_of(&mode),
-            usage: BufferUsages::STORAGE,
+            size: size_of::<u32>() as u64,
+            usage: uniform_usage,
+            mapped_at_creation: false,
         });
-        let input_data_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        let N_buffer = device.create_buffer(&BufferDescriptor {
             label: None,
-            contents: cast_slice(&v_in_gpu),
-            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            size: size_of::<u32>() as u64,
+            usage: uniform_usage,
+            mapped_at_creation: false,
         });
-        let output_data_buffer = device.create_buffer(&BufferDescriptor {
+        let params_buffer = device.create_buffer(&BufferDescriptor {
             label: None,
-            size: input_data_buffer.size(),
-            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            size: (MAX_DEPTHS * params_align) as u64,
+            usage: uniform_usage,
             mapped_at_creation: false,
         });
-        let download_buffer_a = device.create_buffer(&BufferDescriptor {
+        let op_buffer = device.create_buffer(&BufferDescriptor {
             label: None,
-            size: input_data_buffer.size(),
-            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            size: size_of::<u32>() as u64,
+            usage: uniform_usage,
             mapped_at_creation: false,
         });
-        let download_buffer_b = device.create_buffer(&BufferDescriptor {
+        let is_signed_buffer = device.create_buffer(&BufferDescriptor {
             label: None,
-            size: input_data_buffer.size(),
-            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            size: size_of::<u32>() as u64,
+            usage: uniform_usage,
             mapped_at_creation: false,
         });
 
@@ -185,42 +181,52 @@ impl Scan {
             },
             count: None,
         };
-        let input_entry_d_end = BindGroupLayoutEntry {
+        let input_entry_params = BindGroupLayoutEntry {
             binding: 2,
             visibility: ShaderStages::COMPUTE,
             ty: BindingType::Buffer {
                 ty: BufferBindingType::Uniform,
-                min_binding_size: Some(NonZero::new(size_of::<u32>() as u64).unwrap()),
-                has_dynamic_offset: false,
+                min_binding_size: Some(NonZero::new(2 * size_of::<u32>() as u64).unwrap()),
+                has_dynamic_offset: true,
             },
             count: None,
         };
-        let input_entry_mode = BindGroupLayoutEntry {
-            binding: 3,
+        let input_entry_data = BindGroupLayoutEntry {
+            binding: 4,
             visibility: ShaderStages::COMPUTE,
             ty: BindingType::Buffer {
                 ty: BufferBindingType::Storage { read_only: false },
-                min_binding_size: Some(NonZero::new(size_of::<u32>() as u64).unwrap()),
+                min_binding_size: Some(NonZero::new(size_of::<i64>() as u64).unwrap()),
                 has_dynamic_offset: false,
             },
             count: None,
         };
-        let input_entry_data = BindGroupLayoutEntry {
-            binding: 4,
+        let output_entry_data = BindGroupLayoutEntry {
+            binding: 5,
             visibility: ShaderStages::COMPUTE,
             ty: BindingType::Buffer {
                 ty: BufferBindingType::Storage { read_only: false },
-                min_binding_size: Some(NonZero::new(size_of::<i32>() as u64).unwrap()),
+                min_binding_size: Some(NonZero::new(size_of::<i64>() as u64).unwrap()),
                 has_dynamic_offset: false,
             },
             count: None,
         };
-        let output_entry_data = BindGroupLayoutEntry {
-            binding: 5,
+        let input_entry_op = BindGroupLayoutEntry {
+            binding: 6,
             visibility: ShaderStages::COMPUTE,
             ty: BindingType::Buffer {
-                ty: BufferBindingType::Storage { read_only: false },
-                min_binding_size: Some(NonZero::new(size_of::<i32>() as u64).unwrap()),
+                ty: BufferBindingType::Uniform,
+                min_binding_size: Some(NonZero::new(size_of::<u32>() as u64).unwrap()),
+                has_dynamic_offset: false,
+            },
+            count: None,
+        };
+        let input_entry_is_signed = BindGroupLayoutEntry {
+            binding: 7,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                min_binding_size: Some(NonZero::new(size_of::<u32>() as u64).unwrap()),
                 has_dynamic_offset: false,
             },
             count: None,
@@ -230,10 +236,11 @@ impl Scan {
             entries: &[
                 input_entry_n,
                 input_entry_N,
-                input_entry_d_end,
-                input_entry_mode,
+                input_entry_params,
                 input_entry_data,
                 output_entry_data,
+                input_entry_op,
+                input_entry_is_signed,
             ],
         });
         let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
@@ -250,21 +257,95 @@ impl Scan {
             cache: None,
         });
 
+        Ok(Self {
+            verbose,
+            device,
+            queue,
+            bind_group_layout,
+            pipeline,
+            params_align,
+            n_buffer,
+            N_buffer,
+            params_buffer,
+            op_buffer,
+            is_signed_buffer,
+            buffers: None,
+        })
+    }
+
+    /// Get the max size (in bytes) of a storage buffer supported by the device.
+    fn get_max_buf_size(&self) -> usize {
+        let limits = self.device.limits();
+        cmp::min(
+            limits.max_storage_buffer_binding_size as u64,
+            limits.max_buffer_size,
+        ) as usize
+    }
+
+    /// Split `num_workgroups` over two dimensions of at most `max_workgroups` workgroups each.
+    fn get_dispatch_size(num_workgroups: usize, max_workgroups: usize) -> (u32, u32) {
+        let n_x = cmp::max(cmp::min(num_workgroups, max_workgroups), 1);
+        let n_y = usize::div_ceil(num_workgroups, n_x);
+        (n_x as u32, cmp::max(n_y, 1) as u32)
+    }
+
+    /// Ensure the buffers fit an input of length `n`, reallocating them if `n` exceeds their
+    /// capacity.
+    fn reserve(&mut self, n: usize) -> Result<(), String> {
+        let max_buf_size = self.get_max_buf_size();
+        if n * size_of::<i64>() > max_buf_size {
+            return Err(format!(
+                "Expected input length ({}) within the max supported GPU buffer size ({} bytes)",
+                n, max_buf_size
+            ));
+        }
+        let capacity = self.buffers.as_ref().map_or(0, |buffers| buffers.capacity);
+        if n > capacity {
+            let capacity = cmp::min(n.next_power_of_two(), max_buf_size / size_of::<i64>());
+            if self.verbose {
+                eprintln!("Growing GPU buffers to capacity {}", capacity);
+            }
+            self.buffers = Some(self.create_buffers(capacity));
+        }
+        Ok(())
+    }
+
+    fn create_buffers(&self, capacity: usize) -> GpuBuffers {
+        let size = (capacity * size_of::<i64>()) as u64;
+        let input_data_buffer = self.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let output_data_buffer = self.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let download_buffer = self.device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
         let input_bind_group_n = BindGroupEntry {
             binding: 0,
-            resource: input_n_buffer.as_entire_binding(),
+            resource: self.n_buffer.as_entire_binding(),
         };
         let input_bind_group_N = BindGroupEntry {
             binding: 1,
-            resource: input_N_buffer.as_entire_binding(),
+            resource: self.N_buffer.as_entire_binding(),
         };
-        let input_bind_group_d_end = BindGroupEntry {
+        let input_bind_group_params = BindGroupEntry {
             binding: 2,
-            resource: input_d_end_buffer.as_entire_binding(),
-        };
-        let input_bind_group_mode = BindGroupEntry {
-            binding: 3,
-            resource: input_mode_buffer.as_entire_binding(),
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer: &self.params_buffer,
+                offset: 0,
+                size: NonZero::new(2 * size_of::<u32>() as u64),
+            }),
         };
         let input_bind_group_data = BindGroupEntry {
             binding: 4,
@@ -274,81 +355,203 @@ impl Scan {
             binding: 5,
             resource: output_data_buffer.as_entire_binding(),
         };
-        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        let input_bind_group_op = BindGroupEntry {
+            binding: 6,
+            resource: self.op_buffer.as_entire_binding(),
+        };
+        let input_bind_group_is_signed = BindGroupEntry {
+            binding: 7,
+            resource: self.is_signed_buffer.as_entire_binding(),
+        };
+        let bind_group = self.device.create_bind_group(&BindGroupDescriptor {
             label: None,
-            layout: &bind_group_layout,
+            layout: &self.bind_group_layout,
             entries: &[
                 input_bind_group_n,
                 input_bind_group_N,
-                input_bind_group_d_end,
-                input_bind_group_mode,
+                input_bind_group_params,
                 input_bind_group_data,
                 output_bind_group_data,
+                input_bind_group_op,
+                input_bind_group_is_signed,
             ],
         });
 
-        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+        GpuBuffers {
+            capacity,
+            input_data_buffer,
+            output_data_buffer,
+            download_buffer,
+            bind_group,
+        }
+    }
+
+    /// Run the scan with operator code `op` over `v_in_gpu` (already shifted for the exclusive
+    /// scan), and copy the result to `v_out_gpu`.
+    /// The elements are compared as signed (resp. unsigned) integers if `is_signed` is set
+    /// (resp. unset).
+    pub fn run<const N: usize>(
+        &mut self,
+        op: u32,
+        is_signed: bool,
+        v_in_gpu: &[i64],
+        v_out_gpu: &mut [i64],
+    ) -> Result<(), String> {
+        let n = v_in_gpu.len();
+        let num_workgroups = usize::div_ceil(usize::div_ceil(n, N), WORKGROUP_SIZE);
+        let d_end = n.next_power_of_two().trailing_zeros() as usize;
+        let size = size_of_val(v_in_gpu) as u64;
+
+        // NOTE: Pack the parameters of every depth, aligned for use as dynamic offsets
+        let params_align = self.params_align;
+        let mut params = vec![0_u8; d_end * params_align];
+        let mut mode = DoubleBufferMode::default();
+        for d in 0..d_end {
+            let offset = 1_u32 << d; // 2^d
+            let k = d * params_align;
+            params[k..(k + 4)].copy_from_slice(bytes_of(&offset));
+            params[(k + 4)..(k + 8)].copy_from_slice(bytes_of(&(mode as u32)));
+            mode.swap();
+        }
+
+        self.reserve(n)?;
+        let max_workgroups = self.device.limits().max_compute_workgroups_per_dimension as usize;
+        let (n_x, n_y) = Self::get_dispatch_size(num_workgroups, max_workgroups);
+
+        self.queue
+            .write_buffer(&self.n_buffer, 0, bytes_of(&(n as u32)));
+        self.queue
+            .write_buffer(&self.N_buffer, 0, bytes_of(&(N as u32)));
+        self.queue.write_buffer(&self.op_buffer, 0, bytes_of(&op));
+        self.queue
+            .write_buffer(&self.is_signed_buffer, 0, bytes_of(&(is_signed as u32)));
+        if !params.is_empty() {
+            self.queue.write_buffer(&self.params_buffer, 0, &params);
+        }
+
+        let buffers = self.buffers.as_ref().unwrap();
+        self.queue
+            .write_buffer(&buffers.input_data_buffer, 0, cast_slice(v_in_gpu));
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor { label: None });
 
         encoder.copy_buffer_to_buffer(
-            &input_data_buffer,
+            &buffers.input_data_buffer,
             0,
-            &output_data_buffer,
+            &buffers.output_data_buffer,
             0,
-            output_data_buffer.size(),
+            size,
         );
 
         let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
             label: None,
             timestamp_writes: None,
         });
-        compute_pass.set_pipeline(&pipeline);
-        compute_pass.set_bind_group(0, &bind_group, &[]);
-        compute_pass.dispatch_workgroups(num_chunks as u32, 1, 1);
+        compute_pass.set_pipeline(&self.pipeline);
+        for d in 0..d_end {
+            compute_pass.set_bind_group(0, &buffers.bind_group, &[(d * params_align) as u32]);
+            compute_pass.dispatch_workgroups(n_x, n_y, 1);
+        }
         drop(compute_pass);
 
-        encoder.copy_buffer_to_buffer(
-            &output_data_buffer,
-            0,
-            &download_buffer_a,
-            0,
-            output_data_buffer.size(),
-        );
-        encoder.copy_buffer_to_buffer(
-            &input_data_buffer,
-            0,
-            &download_buffer_b,
-            0,
-            input_data_buffer.size(),
-        );
+        let result_buffer = match mode {
+            DoubleBufferMode::A => &buffers.input_data_buffer,
+            DoubleBufferMode::B => &buffers.output_data_buffer,
+        };
+        encoder.copy_buffer_to_buffer(result_buffer, 0, &buffers.download_buffer, 0, size);
 
         let command_buffer = encoder.finish();
-        queue.submit([command_buffer]);
-
-        let buffer_slice_a = download_buffer_a.slice(..);
-        let buffer_slice_b = download_buffer_b.slice(..);
-        buffer_slice_a.map_async(MapMode::Read, |_| {});
-        buffer_slice_b.map_async(MapMode::Read, |_| {});
-
-        let _ = device.poll(Maintain::Wait);
-        let data_a = buffer_slice_a.get_mapped_range();
-        let data_b = buffer_slice_b.get_mapped_range();
-        if d_end % 2 == 1 {
-            copy_casted::<i32, T>(cast_slice(&data_a), v_out)?;
-        } else {
-            copy_casted::<i32, T>(cast_slice(&data_b), v_out)?;
+        self.queue.submit([command_buffer]);
+
+        let buffer_slice = buffers.download_buffer.slice(..size);
+        buffer_slice.map_async(MapMode::Read, |_| {});
+
+        let _ = self.device.poll(Maintain::Wait);
+        {
+            let data = buffer_slice.get_mapped_range();
+            v_out_gpu.copy_from_slice(cast_slice(&data));
         }
+        buffers.download_buffer.unmap();
+
+        Ok(())
+    }
+}
+
+impl Scan {
+    /// Implement the parallel GPU exclusive/inclusive scan algorithm.
+    /// Elements are scanned as 64-bit integers, sign- (resp. zero-) extended from signed
+    /// (resp. unsigned) `T`, so that the truncated results match the CPU implementations.
+    pub fn process<T, O, const N: usize>(
+        &self,
+        kind: ScanKind,
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IBottom + ICast<i64> + IDisplay + IZero + Pod + Send,
+        O: IOperator<T>,
+        i64: ICast<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        let op = O::KIND as u32;
+        let identity: i64 = O::identity().cast();
+        let is_signed = T::BOTTOM < T::ZERO;
 
         if self.verbose {
-            eprintln!("data_a: {:?}", &data_a[..]);
-            eprintln!("data_b: {:?}", &data_b[..]);
+            eprintln!("Starting par_gpu_naive_dbl");
+            eprintln!("v_in: {:?}", v_in);
+            eprintln!("v_out: {:?}", v_out);
         }
 
-        Ok(())
+        Self::check_args(n_in, n_out)?;
+        if N == 0 {
+            return Err("Expected non-zero number of elements per invocation".to_string());
+        }
+        if n_out == 0 {
+            return Ok(());
+        }
+
+        let mut v_in_gpu: Vec<i64> = vec![identity; n_in];
+        match kind {
+            ScanKind::Exclusive => {
+                copy_casted::<T, i64>(&v_in[..(n_in - 1)], &mut v_in_gpu[1..n_in])?
+            }
+            ScanKind::Inclusive => copy_casted::<T, i64>(v_in, &mut v_in_gpu)?,
+        }
+
+        if self.verbose {
+            eprintln!("v_in_gpu: {:?}", v_in_gpu);
+        }
+
+        let mut context = self
+            .context
+            .lock()
+            .or(Err("Failed to acquire GPU context".to_string()))?;
+        if context.is_none() {
+            *context = Some(GpuContext::new(self.verbose)?);
+        }
+        let mut v_out_gpu: Vec<i64> = vec![identity; n_out];
+        context
+            .as_mut()
+            .unwrap()
+            .run::<N>(op, is_signed, &v_in_gpu, &mut v_out_gpu)?;
+
+        if self.verbose {
+            eprintln!("v_out_gpu: {:?}", v_out_gpu);
+        }
+
+        copy_casted::<i64, T>(&v_out_gpu, v_out)
     }
 }
 
 impl IScan for Scan {
     fn new(verbose: bool) -> Self {
-        Self { verbose }
+        Self {
+            verbose,
+            context: Mutex::new(None),
+        }
     }
 }
@@ -1,56 +1,130 @@
 // Copyright 2025, Giordano Salvador
 // SPDX-License-Identifier: BSD-3-Clause
 
+// NOTE: Must match `WORKGROUP_SIZE` in `lib.rs`
+const WORKGROUP_SIZE: u32 = 64u;
+
+// NOTE: The parameters of a single depth, passed per dispatch via a dynamic offset
+struct Params {
+    offset: u32,
+    mode: u32,
+}
+
 @group(0) @binding(0)
 var<uniform> n: u32;
 @group(0) @binding(1)
 var<uniform> N: u32;
 @group(0) @binding(2)
-var<uniform> d_end: u32;
-@group(0) @binding(3)
-var<storage, read_write> mode: u32;
+var<uniform> params: Params;
 @group(0) @binding(4)
-var<storage, read_write> input: array<i32>;
+var<storage, read_write> input: array<vec2<u32>>;
 @group(0) @binding(5)
-var<storage, read_write> output: array<i32>;
+var<storage, read_write> output: array<vec2<u32>>;
+@group(0) @binding(6)
+var<uniform> op: u32;
+@group(0) @binding(7)
+var<uniform> is_signed: u32;
+
+// NOTE: Operator codes follow the discriminants of `support::OperatorKind`.
+// Elements are 64-bit integers stored as (lo, hi) pairs of u32 words, and the operators wrap on
+// overflow like those of `support`.
+fn combine(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    switch (op) {
+        case 1u: {
+            return mul64(a, b);
+        }
+        case 2u: {
+            return select(b, a, less64(a, b));
+        }
+        case 3u: {
+            return select(a, b, less64(a, b));
+        }
+        case 4u: {
+            return a & b;
+        }
+        case 5u: {
+            return a | b;
+        }
+        case 6u: {
+            return a ^ b;
+        }
+        default: {
+            return add64(a, b);
+        }
+    }
+}
+
+fn add64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let lo = a.x + b.x;
+    let carry = select(0u, 1u, lo < a.x);
+    return vec2<u32>(lo, a.y + b.y + carry);
+}
+
+// Returns the full 64-bit product of two 32-bit words, from the products of their 16-bit halves.
+fn mul_wide(a: u32, b: u32) -> vec2<u32> {
+    let a_lo = a & 0xFFFFu;
+    let a_hi = a >> 16u;
+    let b_lo = b & 0xFFFFu;
+    let b_hi = b >> 16u;
+    let p_lo = a_lo * b_lo;
+    let p_mid_a = a_lo * b_hi;
+    let p_mid_b = a_hi * b_lo;
+    let p_hi = a_hi * b_hi;
+    let p_mid = p_mid_a + p_mid_b;
+    let carry_mid = select(0u, 0x10000u, p_mid < p_mid_a);
+    let lo = p_lo + (p_mid << 16u);
+    let carry_lo = select(0u, 1u, lo < p_lo);
+    return vec2<u32>(lo, p_hi + (p_mid >> 16u) + carry_mid + carry_lo);
+}
+
+fn mul64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
+    let p = mul_wide(a.x, b.x);
+    return vec2<u32>(p.x, p.y + a.x * b.y + a.y * b.x);
+}
+
+// Compares the elements as signed (resp. unsigned) integers if `is_signed` is set (resp. unset).
+fn less64(a: vec2<u32>, b: vec2<u32>) -> bool {
+    if (a.y != b.y) {
+        if (is_signed != 0u) {
+            return bitcast<i32>(a.y) < bitcast<i32>(b.y);
+        }
+        return a.y < b.y;
+    }
+    return a.x < b.x;
+}
 
-@compute @workgroup_size(64)
+// NOTE: Computes a single depth of the scan, since `storageBarrier()` only synchronizes the
+// invocations within a workgroup; the depths are ordered by issuing one dispatch per depth.
+@compute @workgroup_size(WORKGROUP_SIZE)
 fn scan(
-    @builtin(global_invocation_id) global_id: vec3<u32>
+    @builtin(local_invocation_id) lid: vec3<u32>,
+    @builtin(workgroup_id) wid: vec3<u32>,
+    @builtin(num_workgroups) num_wgs: vec3<u32>,
 ) {
-    let idx = global_id.x;
-    for (var d: u32 = 0; d < d_end; d++) {
-        if (idx < n) {
-            let offset = u32(1) << d;
-            let k_begin = idx * N;
-            let k_end_clamp = min(n, k_begin + N);
-            for (var k: u32 = k_begin; k < k_end_clamp; k++) {
-                if (mode == 0 && k >= offset) {
-                    let j = k - offset;
-                    let a = input[j];
-                    let b = input[k];
-                    output[k] = a + b;
-                } else if (mode == 1 && k >= offset) {
-                    let j = k - offset;
-                    let a = output[j];
-                    let b = output[k];
-                    input[k] = a + b;
-                } else if (mode == 0) {
-                    let a = input[k];
-                    output[k] = a;
-                } else if (mode == 1) {
-                    let a = output[k];
-                    input[k] = a;
-                }
-            }
-        }
-        storageBarrier();
-        if (idx == 0) {
-            if (mode == 0) {
-                mode = 1;
-            } else if (mode == 1) {
-                mode = 0;
-            }
+    // NOTE: The workgroups are dispatched over two dimensions for inputs exceeding the max
+    // number of workgroups per dimension.
+    let idx = (wid.y * num_wgs.x + wid.x) * WORKGROUP_SIZE + lid.x;
+    let offset = params.offset;
+    let mode = params.mode;
+    let k_begin = idx * N;
+    let k_end_clamp = min(n, k_begin + N);
+    for (var k: u32 = k_begin; k < k_end_clamp; k++) {
+        if (mode == 0 && k >= offset) {
+            let j = k - offset;
+            let a = input[j];
+            let b = input[k];
+            output[k] = combine(a, b);
+        } else if (mode == 1 && k >= offset) {
+            let j = k - offset;
+            let a = output[j];
+            let b = output[k];
+            input[k] = combine(a, b);
+        } else if (mode == 0) {
+            let a = input[k];
+            output[k] = a;
+        } else if (mode == 1) {
+            let a = output[k];
+            input[k] = a;
         }
     }
 }
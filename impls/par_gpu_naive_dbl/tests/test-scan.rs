@@ -3,33 +3,52 @@
 
 #[cfg(test)]
 mod tests {
+    use support::IScan;
+    use support::OpAdd;
+    use support::ScanKind;
+    use test_scan::gen_ramp_in;
+    use test_scan::gen_ramp_out;
+    use test_scan::golden_tests_int;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
     use test_scan::test_body;
-    use test_scan::N100_1_IN;
-    use test_scan::N100_1_OUT;
-    use test_scan::N15_1_IN;
-    use test_scan::N15_1_OUT;
-    use test_scan::N16_1_IN;
-    use test_scan::N16_1_OUT;
-    use test_scan::N8_1_IN;
-    use test_scan::N8_1_OUT;
 
-    #[test]
-    fn test_par_gpu_naive_2x_n8_1() {
-        test_body(5, "ScanParGPUNaive2x", &N8_1_IN, &N8_1_OUT);
-    }
+    scan_fns!(par_gpu, par_gpu_naive_dbl::Scan; scan);
+
+    golden_tests_int!("ScanParGPUNaive2x", scan, 4);
+
+    prop_tests!("ScanParGPUNaive2x", scan, 4);
 
     #[test]
-    fn test_par_gpu_naive_2x_n15_1() {
-        test_body(5, "ScanParGPUNaive2x", &N15_1_IN, &N15_1_OUT);
+    fn test_par_gpu_naive_2x_n4099_ramp() {
+        test_body(
+            "ScanParGPUNaive2x",
+            scan::<_, OpAdd, 4>(ScanKind::Exclusive),
+            &gen_ramp_in(4099),
+            &gen_ramp_out(4099),
+        );
     }
 
     #[test]
-    fn test_par_gpu_naive_2x_n16_1() {
-        test_body(5, "ScanParGPUNaive2x", &N16_1_IN, &N16_1_OUT);
+    fn test_par_gpu_naive_2x_n16384_ramp() {
+        test_body(
+            "ScanParGPUNaive2x",
+            scan::<_, OpAdd, 4>(ScanKind::Exclusive),
+            &gen_ramp_in(16384),
+            &gen_ramp_out(16384),
+        );
     }
 
     #[test]
-    fn test_par_gpu_naive_2x_n100_1() {
-        test_body(5, "ScanParGPUNaive2x", &N100_1_IN, &N100_1_OUT);
+    fn test_par_gpu_naive_2x_reuse_context() {
+        // NOTE: Reuse the GPU context of a single scan object across growing and shrinking inputs
+        let scan_obj = par_gpu_naive_dbl::Scan::new(false);
+        for n in [100, 5000, 16, 5001] {
+            let v_in = gen_ramp_in(n);
+            let mut v_out = vec![0; n];
+            let result = scan_obj.process::<i64, OpAdd, 4>(ScanKind::Exclusive, &v_in, &mut v_out);
+            assert_eq!(result, Ok(()));
+            assert!(v_out == gen_ramp_out(n));
+        }
     }
 }
@@ -3,9 +3,15 @@
 
 #![allow(dead_code)]
 
-use support::IAdd;
+use support::alloc;
+use support::segment;
+use support::unsegment;
+use support::Flagged;
 use support::IDisplay;
+use support::IOperator;
 use support::IScan;
+use support::OpSegmented;
+use support::ScanKind;
 
 #[derive(Clone, Copy)]
 pub struct Scan {
@@ -13,20 +19,55 @@ pub struct Scan {
 }
 
 impl Scan {
-    /// Implement the sequential exclusive scan algorithm
-    pub fn process<T>(&self, def: T, v_in: &[T], v_out: &mut [T]) -> Result<(), String>
+    /// Implement the sequential exclusive/inclusive scan algorithm
+    pub fn process<T, O>(&self, kind: ScanKind, v_in: &[T], v_out: &mut [T]) -> Result<(), String>
     where
-        T: Copy + Eq + IAdd + IDisplay,
+        T: Copy + IDisplay,
+        O: IOperator<T>,
     {
         let n_in = v_in.len();
         let n_out = v_out.len();
         Self::check_args(n_in, n_out)?;
-        v_out[0] = def;
-        for k in 1..n_out {
-            v_out[k] = v_in[k - 1] + v_out[k - 1];
+        match kind {
+            ScanKind::Exclusive => {
+                v_out[0] = O::identity();
+                for k in 1..n_out {
+                    v_out[k] = O::combine(v_out[k - 1], v_in[k - 1]);
+                }
+            }
+            ScanKind::Inclusive => {
+                v_out[0] = v_in[0];
+                for k in 1..n_out {
+                    v_out[k] = O::combine(v_out[k - 1], v_in[k]);
+                }
+            }
         }
         Ok(())
     }
+
+    /// Implement the segmented sequential scan algorithm via the flagged operator transformation,
+    /// where the scan restarts at every element whose head flag in `flags` is set
+    pub fn process_segmented<T, O>(
+        &self,
+        kind: ScanKind,
+        flags: &[bool],
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        Self::check_args(n_in, n_out)?;
+        Self::check_flags(n_in, flags.len())?;
+        let mut v_in_seg = alloc(n_in, OpSegmented::<O>::identity());
+        let mut v_out_seg = alloc(n_out, OpSegmented::<O>::identity());
+        segment(kind, O::identity(), flags, v_in, &mut v_in_seg)?;
+        self.process::<Flagged<T>, OpSegmented<O>>(ScanKind::Inclusive, &v_in_seg, &mut v_out_seg)?;
+        unsegment(&v_out_seg, v_out)
+    }
 }
 
 impl IScan for Scan {
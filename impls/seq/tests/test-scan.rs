@@ -3,33 +3,116 @@
 
 #[cfg(test)]
 mod tests {
+    use support::compensate;
+    use support::scan_pairwise;
+    use support::uncompensate;
+    use support::Compensated;
+    use support::IScan;
+    use support::OpAdd;
+    use support::OpAddCompensated;
+    use support::ScanKind;
+    use test_scan::gen_sensor_in;
+    use test_scan::gen_sensor_ref;
+    use test_scan::get_max_rel_error;
+    use test_scan::golden_tests;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
     use test_scan::test_body;
-    use test_scan::N100_1_IN;
-    use test_scan::N100_1_OUT;
-    use test_scan::N15_1_IN;
-    use test_scan::N15_1_OUT;
     use test_scan::N16_1_IN;
-    use test_scan::N16_1_OUT;
-    use test_scan::N8_1_IN;
-    use test_scan::N8_1_OUT;
+    use test_scan::N16_1_SEG_HEADS;
+    use test_scan::N16_1_SEG_INCL_OUT;
+    use test_scan::N16_1_SEG_OUT;
+
+    scan_fns!(seq, seq::Scan; scan, scan_segmented);
+
+    golden_tests!("ScanSeq", scan);
+
+    prop_tests!("ScanSeq", scan);
 
     #[test]
-    fn test_seq_n8_1() {
-        test_body(0, "ScanSeq", &N8_1_IN, &N8_1_OUT);
+    fn test_seq_segmented_n16_1() {
+        test_body(
+            "ScanSeq",
+            scan_segmented::<_, OpAdd>(ScanKind::Exclusive, &N16_1_SEG_HEADS),
+            &N16_1_IN,
+            &N16_1_SEG_OUT,
+        );
     }
 
     #[test]
-    fn test_seq_n15_1() {
-        test_body(0, "ScanSeq", &N15_1_IN, &N15_1_OUT);
+    fn test_seq_segmented_inclusive_n16_1() {
+        test_body(
+            "ScanSeq",
+            scan_segmented::<_, OpAdd>(ScanKind::Inclusive, &N16_1_SEG_HEADS),
+            &N16_1_IN,
+            &N16_1_SEG_INCL_OUT,
+        );
     }
 
     #[test]
-    fn test_seq_n16_1() {
-        test_body(0, "ScanSeq", &N16_1_IN, &N16_1_OUT);
+    fn test_seq_segmented_flags_mismatch() {
+        let scan_obj = seq::Scan::new(false);
+        let v_in = [1, 2, 3, 4];
+        let mut v_out = [0; 4];
+        let result = scan_obj.process_segmented::<i64, OpAdd>(
+            ScanKind::Exclusive,
+            &[true, false, true],
+            &v_in,
+            &mut v_out,
+        );
+        assert_eq!(
+            result,
+            Err(
+                "Expected segment head flags of length 4 (the input length), found length 3"
+                    .to_string()
+            )
+        );
     }
 
     #[test]
-    fn test_seq_n100_1() {
-        test_body(0, "ScanSeq", &N100_1_IN, &N100_1_OUT);
+    fn test_seq_accuracy_f32_n1048576_sensor() {
+        let n = 1 << 20;
+        let v_in = gen_sensor_in(n);
+        let v_ref = gen_sensor_ref(&v_in);
+        let scan_obj = seq::Scan::new(false);
+
+        let mut v_out = vec![0.0; n];
+        let result = scan_obj.process::<f32, OpAdd>(ScanKind::Inclusive, &v_in, &mut v_out);
+        assert_eq!(result, Ok(()));
+        let err_plain = get_max_rel_error(&v_out, &v_ref);
+
+        let mut v_out = vec![0.0; n];
+        let mut v_in_comp = vec![Compensated::default(); n];
+        let mut v_out_comp = vec![Compensated::default(); n];
+        assert_eq!(compensate(&v_in, &mut v_in_comp), Ok(()));
+        let result = scan_obj.process::<Compensated<f32>, OpAddCompensated>(
+            ScanKind::Inclusive,
+            &v_in_comp,
+            &mut v_out_comp,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(uncompensate(&v_out_comp, &mut v_out), Ok(()));
+        let err_comp = get_max_rel_error(&v_out, &v_ref);
+
+        let mut v_out = vec![0.0; n];
+        let result = scan_pairwise::<f32, OpAdd, _>(
+            ScanKind::Inclusive,
+            128,
+            &v_in,
+            &mut v_out,
+            |leaf_in, leaf_out| {
+                scan_obj.process::<f32, OpAdd>(ScanKind::Inclusive, leaf_in, leaf_out)
+            },
+        );
+        assert_eq!(result, Ok(()));
+        let err_pairwise = get_max_rel_error(&v_out, &v_ref);
+
+        eprintln!(
+            "Max relative error: plain {:e}, compensated {:e}, pairwise {:e}",
+            err_plain, err_comp, err_pairwise
+        );
+        assert!(err_comp <= f32::EPSILON as f64);
+        assert!(err_pairwise <= 16.0 * f32::EPSILON as f64);
+        assert!(err_plain > err_pairwise);
     }
 }
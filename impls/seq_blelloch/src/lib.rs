@@ -0,0 +1,95 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use support::alloc;
+use support::copy;
+use support::IDisplay;
+use support::IOperator;
+use support::IScan;
+use support::ScanKind;
+
+#[derive(Clone, Copy)]
+pub struct Scan {
+    verbose: bool,
+}
+
+impl Scan {
+    /// Implement the sequential work-efficient (Blelloch) exclusive/inclusive scan algorithm
+    pub fn process<T, O>(&self, kind: ScanKind, v_in: &[T], v_out: &mut [T]) -> Result<(), String>
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        Self::check_args(n_in, n_out)?;
+        // NOTE: Pad the tree to the next power of two with the identity element
+        let n_tree = n_out.next_power_of_two();
+        let d_end = n_tree.trailing_zeros() as usize;
+        let mut tree = alloc(n_tree, O::identity());
+        copy(v_in, &mut tree[0..n_out])?;
+        if self.verbose {
+            eprintln!("Computing tree depth [0..{}) for {} leaves", d_end, n_tree);
+            eprintln!("tmp: {:?}", tree);
+            eprintln!("Up-sweep:");
+        }
+        for d in 0..d_end {
+            if self.verbose {
+                eprintln!("Depth {}:", d);
+            }
+            let offset = 1 << d; // 2^d
+            let stride = offset << 1; // 2^(d+1)
+            for k in ((stride - 1)..n_tree).step_by(stride) {
+                let j = k - offset;
+                let a = tree[j];
+                let b = tree[k];
+                if self.verbose {
+                    eprintln!("*   ({},{},{}): {} + {}", k, j, k, a, b);
+                }
+                tree[k] = O::combine(a, b);
+            }
+            if self.verbose {
+                eprintln!("tmp: {:?}", tree);
+            }
+        }
+        tree[n_tree - 1] = O::identity();
+        if self.verbose {
+            eprintln!("Down-sweep:");
+        }
+        for d in (0..d_end).rev() {
+            if self.verbose {
+                eprintln!("Depth {}:", d);
+            }
+            let offset = 1 << d; // 2^d
+            let stride = offset << 1; // 2^(d+1)
+            for k in ((stride - 1)..n_tree).step_by(stride) {
+                let j = k - offset;
+                let a = tree[k];
+                let b = tree[j];
+                if self.verbose {
+                    eprintln!("*   ({},{},{}): {} + {}", k, k, j, a, b);
+                }
+                tree[j] = a;
+                tree[k] = O::combine(a, b);
+            }
+            if self.verbose {
+                eprintln!("tmp: {:?}", tree);
+            }
+        }
+        match kind {
+            ScanKind::Exclusive => copy(&tree[0..n_out], v_out)?,
+            ScanKind::Inclusive => {
+                for k in 0..n_out {
+                    v_out[k] = O::combine(tree[k], v_in[k]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl IScan for Scan {
+    fn new(verbose: bool) -> Self {
+        Self { verbose }
+    }
+}
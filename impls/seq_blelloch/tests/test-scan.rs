@@ -0,0 +1,15 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+#[cfg(test)]
+mod tests {
+    use test_scan::golden_tests;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
+
+    scan_fns!(seq, seq_blelloch::Scan; scan);
+
+    golden_tests!("ScanSeqBlelloch", scan);
+
+    prop_tests!("ScanSeqBlelloch", scan);
+}
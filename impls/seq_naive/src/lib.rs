@@ -1,10 +1,11 @@
 // Copyright 2025, Giordano Salvador
 // SPDX-License-Identifier: BSD-3-Clause
 
-use support::copy;
-use support::IAdd;
+use support::copy_scan_input;
 use support::IDisplay;
+use support::IOperator;
 use support::IScan;
+use support::ScanKind;
 
 #[derive(Clone, Copy)]
 pub struct Scan {
@@ -12,17 +13,17 @@ pub struct Scan {
 }
 
 impl Scan {
-    /// Implement the sequential naive parallel exclusive scan algorithm
-    pub fn process<T>(&self, def: T, v_in: &[T], v_out: &mut [T]) -> Result<(), String>
+    /// Implement the sequential naive parallel exclusive/inclusive scan algorithm
+    pub fn process<T, O>(&self, kind: ScanKind, v_in: &[T], v_out: &mut [T]) -> Result<(), String>
     where
-        T: Copy + Eq + IAdd + IDisplay,
+        T: Copy + IDisplay,
+        O: IOperator<T>,
     {
         let n_in = v_in.len();
         let n_out = v_out.len();
         let d_end = (n_out as f32).log2().ceil() as usize;
         Self::check_args(n_in, n_out)?;
-        copy(&v_in[..(n_in - 1)], &mut v_out[1..n_out])?;
-        v_out[0] = def;
+        copy_scan_input(kind, O::identity(), v_in, v_out)?;
         if self.verbose {
             eprintln!("Computing tree depth [0..{})", d_end);
             eprintln!("tmp: {:?}", v_out);
@@ -42,7 +43,7 @@ impl Scan {
                     if self.verbose {
                         eprintln!("*   ({},{},{}): {} + {}", k, j, k, a, b);
                     }
-                    v_out[k] = a + b;
+                    v_out[k] = O::combine(a, b);
                 }
             }
             if self.verbose {
@@ -1,11 +1,18 @@
 // Copyright 2025, Giordano Salvador
 // SPDX-License-Identifier: BSD-3-Clause
 
+use support::alloc;
 use support::copy;
+use support::copy_scan_input;
+use support::segment;
+use support::unsegment;
 use support::DoubleBufferMode;
-use support::IAdd;
+use support::Flagged;
 use support::IDisplay;
+use support::IOperator;
 use support::IScan;
+use support::OpSegmented;
+use support::ScanKind;
 
 #[derive(Clone, Copy)]
 pub struct Scan {
@@ -13,18 +20,18 @@ pub struct Scan {
 }
 
 impl Scan {
-    /// Implement the sequential naive parallel exclusive scan algorithm
-    pub fn process<T>(&self, def: T, v_in: &[T], v_out: &mut [T]) -> Result<(), String>
+    /// Implement the sequential naive parallel exclusive/inclusive scan algorithm
+    pub fn process<T, O>(&self, kind: ScanKind, v_in: &[T], v_out: &mut [T]) -> Result<(), String>
     where
-        T: Copy + Eq + IAdd + IDisplay,
+        T: Copy + IDisplay,
+        O: IOperator<T>,
     {
         let n_in = v_in.len();
         let n_out = v_out.len();
         let d_end = (n_out as f32).log2().ceil() as usize;
         let mut mode = DoubleBufferMode::default();
         Self::check_args(n_in, n_out)?;
-        copy(&v_in[..(n_in - 1)], &mut v_out[1..n_out])?;
-        v_out[0] = def;
+        copy_scan_input(kind, O::identity(), v_in, v_out)?;
         let mut v_out_tmp = v_out.to_vec();
         if self.verbose {
             eprintln!("tmp_a: {:?}", &v_out_tmp[0..n_out]);
@@ -48,7 +55,7 @@ impl Scan {
                     if self.verbose {
                         eprintln!("*   ({},{},{}): {} + {}", k, j, k, a, b);
                     }
-                    buf_b[k] = a + b;
+                    buf_b[k] = O::combine(a, b);
                 } else {
                     let a = buf_a[k];
                     if self.verbose {
@@ -68,6 +75,30 @@ impl Scan {
         }
         Ok(())
     }
+
+    /// Implement the segmented sequential naive parallel scan algorithm via the flagged operator
+    /// transformation, where the scan restarts at every element whose head flag in `flags` is set
+    pub fn process_segmented<T, O>(
+        &self,
+        kind: ScanKind,
+        flags: &[bool],
+        v_in: &[T],
+        v_out: &mut [T],
+    ) -> Result<(), String>
+    where
+        T: Copy + IDisplay,
+        O: IOperator<T>,
+    {
+        let n_in = v_in.len();
+        let n_out = v_out.len();
+        Self::check_args(n_in, n_out)?;
+        Self::check_flags(n_in, flags.len())?;
+        let mut v_in_seg = alloc(n_in, OpSegmented::<O>::identity());
+        let mut v_out_seg = alloc(n_out, OpSegmented::<O>::identity());
+        segment(kind, O::identity(), flags, v_in, &mut v_in_seg)?;
+        self.process::<Flagged<T>, OpSegmented<O>>(ScanKind::Inclusive, &v_in_seg, &mut v_out_seg)?;
+        unsegment(&v_out_seg, v_out)
+    }
 }
 
 impl IScan for Scan {
@@ -3,33 +3,40 @@
 
 #[cfg(test)]
 mod tests {
+    use support::OpAdd;
+    use support::ScanKind;
+    use test_scan::golden_tests;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
     use test_scan::test_body;
-    use test_scan::N100_1_IN;
-    use test_scan::N100_1_OUT;
-    use test_scan::N15_1_IN;
-    use test_scan::N15_1_OUT;
     use test_scan::N16_1_IN;
-    use test_scan::N16_1_OUT;
-    use test_scan::N8_1_IN;
-    use test_scan::N8_1_OUT;
+    use test_scan::N16_1_SEG_HEADS;
+    use test_scan::N16_1_SEG_INCL_OUT;
+    use test_scan::N16_1_SEG_OUT;
 
-    #[test]
-    fn test_seq_naive_2x_n8_1() {
-        test_body(2, "ScanSeqNaive2x", &N8_1_IN, &N8_1_OUT);
-    }
+    scan_fns!(seq, seq_naive_dbl::Scan; scan, scan_segmented);
 
-    #[test]
-    fn test_seq_naive_2x_n15_1() {
-        test_body(2, "ScanSeqNaive2x", &N15_1_IN, &N15_1_OUT);
-    }
+    golden_tests!("ScanSeqNaive2x", scan);
+
+    prop_tests!("ScanSeqNaive2x", scan);
 
     #[test]
-    fn test_seq_naive_2x_n16_1() {
-        test_body(2, "ScanSeqNaive2x", &N16_1_IN, &N16_1_OUT);
+    fn test_seq_naive_2x_segmented_n16_1() {
+        test_body(
+            "ScanSeqNaive2x",
+            scan_segmented::<_, OpAdd>(ScanKind::Exclusive, &N16_1_SEG_HEADS),
+            &N16_1_IN,
+            &N16_1_SEG_OUT,
+        );
     }
 
     #[test]
-    fn test_seq_naive_2x_n100_1() {
-        test_body(2, "ScanSeqNaive2x", &N100_1_IN, &N100_1_OUT);
+    fn test_seq_naive_2x_segmented_inclusive_n16_1() {
+        test_body(
+            "ScanSeqNaive2x",
+            scan_segmented::<_, OpAdd>(ScanKind::Inclusive, &N16_1_SEG_HEADS),
+            &N16_1_IN,
+            &N16_1_SEG_INCL_OUT,
+        );
     }
 }
@@ -20,9 +20,10 @@ use support::alloc_aligned;
 use support::copy_simd;
 use support::rotate_right_simd;
 use support::DoubleBufferMode;
-use support::IAdd;
 use support::IDisplay;
+use support::IOperatorSimd;
 use support::IScan;
+use support::ScanKind;
 
 #[derive(Clone, Copy)]
 pub struct Scan {
@@ -30,27 +31,33 @@ pub struct Scan {
 }
 
 impl Scan {
-    /// Implement the sequential Simd exclusive scan algorithm
-    pub fn process<T, const N: usize>(
+    /// Implement the sequential Simd exclusive/inclusive scan algorithm
+    pub fn process<T, O, const N: usize>(
         &self,
-        def: T,
+        kind: ScanKind,
         v_in: &[T],
         v_out: &mut [T],
     ) -> Result<(), String>
     where
-        T: Copy + IAdd + IDisplay + SimdElement,
+        T: Copy + IDisplay + SimdElement,
         T::Mask: IDisplay + MaskElement,
-        Simd<T, N>: IAdd,
+        O: IOperatorSimd<T, N>,
         LaneCount<N>: SupportedLaneCount,
     {
+        let def = O::identity();
         let n_in = v_in.len();
         let n_out = v_out.len();
         let mut mode = DoubleBufferMode::default();
         Self::check_args(n_in, n_out)?;
         let (buf_a_slice, _backing_store_a) = alloc_aligned::<T, u64>(n_out, def);
         let (buf_b_slice, _backing_store_b) = alloc_aligned::<T, u64>(n_out, def);
-        rotate_right_simd::<T, N>(n_out, def, v_in, buf_a_slice)?;
-        buf_a_slice[0] = def;
+        match kind {
+            ScanKind::Exclusive => {
+                rotate_right_simd::<T, N>(n_out, def, v_in, buf_a_slice)?;
+                buf_a_slice[0] = def;
+            }
+            ScanKind::Inclusive => copy_simd::<T, N>(n_out, def, v_in, buf_a_slice)?,
+        }
         copy_simd::<T, N>(n_out, def, buf_a_slice, buf_b_slice)?;
         if self.verbose {
             eprintln!("tmp_a: {:?}", &buf_a_slice[..]);
@@ -91,7 +98,7 @@ impl Scan {
                     simd_j.cast::<usize>(),
                     simd_def,
                 );
-                let simd_add_kj_true = simd_ld_k.add(simd_ld_j_true);
+                let simd_add_kj_true = O::combine_simd(simd_ld_j_true, simd_ld_k);
                 if self.verbose {
                     eprintln!("simd_n: {:?}", simd_n);
                     eprintln!("simd_offset: {:?}", simd_offset);
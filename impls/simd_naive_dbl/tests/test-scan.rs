@@ -1,35 +1,87 @@
 // Copyright 2025, Giordano Salvador
 // SPDX-License-Identifier: BSD-3-Clause
 
+#![feature(portable_simd)]
+
 #[cfg(test)]
 mod tests {
+    use support::OpAdd;
+    use support::ScanKind;
+    use test_scan::golden::get_in;
+    use test_scan::golden::get_out;
+    use test_scan::golden::N100_1;
+    use test_scan::golden::N15_1;
+    use test_scan::golden::N15_1_INCL;
+    use test_scan::golden::N8_2;
+    use test_scan::golden_tests;
+    use test_scan::prop::test_prop_fold;
+    use test_scan::prop_tests;
+    use test_scan::scan_fns;
     use test_scan::test_body;
-    use test_scan::N100_1_IN;
-    use test_scan::N100_1_OUT;
-    use test_scan::N15_1_IN;
-    use test_scan::N15_1_OUT;
-    use test_scan::N16_1_IN;
-    use test_scan::N16_1_OUT;
-    use test_scan::N8_1_IN;
-    use test_scan::N8_1_OUT;
+
+    scan_fns!(simd, simd_naive_dbl::Scan; scan);
+
+    golden_tests!("ScanSimdNaive2x", scan, 4);
+
+    prop_tests!("ScanSimdNaive2x", scan, 4);
+
+    #[test]
+    fn test_simd_naive_2x_lanes_1_n15_1() {
+        test_body(
+            "ScanSimdNaive2x",
+            scan::<_, OpAdd, 1>(ScanKind::Exclusive),
+            &get_in::<i64>(N15_1),
+            &get_out::<i64>(N15_1),
+        );
+    }
+
+    #[test]
+    fn test_simd_naive_2x_lanes_2_inclusive_n15_1() {
+        test_body(
+            "ScanSimdNaive2x",
+            scan::<_, OpAdd, 2>(ScanKind::Inclusive),
+            &get_in::<i64>(N15_1),
+            &get_out::<i64>(N15_1_INCL),
+        );
+    }
 
     #[test]
-    fn test_simd_naive_2x_n8_1() {
-        test_body(3, "ScanSimdNaive2x", &N8_1_IN, &N8_1_OUT);
+    fn test_simd_naive_2x_lanes_8_n100_1() {
+        test_body(
+            "ScanSimdNaive2x",
+            scan::<_, OpAdd, 8>(ScanKind::Exclusive),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
     }
 
     #[test]
-    fn test_simd_naive_2x_n15_1() {
-        test_body(3, "ScanSimdNaive2x", &N15_1_IN, &N15_1_OUT);
+    fn test_simd_naive_2x_lanes_64_n100_1() {
+        test_body(
+            "ScanSimdNaive2x",
+            scan::<_, OpAdd, 64>(ScanKind::Exclusive),
+            &get_in::<i64>(N100_1),
+            &get_out::<i64>(N100_1),
+        );
     }
 
     #[test]
-    fn test_simd_naive_2x_n16_1() {
-        test_body(3, "ScanSimdNaive2x", &N16_1_IN, &N16_1_OUT);
+    fn test_simd_naive_2x_lanes_16_type_u8_n8_2() {
+        test_body(
+            "ScanSimdNaive2x",
+            scan::<_, OpAdd, 16>(ScanKind::Exclusive),
+            &get_in::<u8>(N8_2),
+            &get_out::<u8>(N8_2),
+        );
     }
 
     #[test]
-    fn test_simd_naive_2x_n100_1() {
-        test_body(3, "ScanSimdNaive2x", &N100_1_IN, &N100_1_OUT);
+    fn test_simd_naive_2x_lanes_8_prop_fold() {
+        test_prop_fold::<i64, OpAdd, _, _>(
+            "ScanSimdNaive2x",
+            8,
+            scan::<i64, OpAdd, 8>(ScanKind::Exclusive),
+            scan::<i64, OpAdd, 8>(ScanKind::Inclusive),
+        );
     }
 }
@@ -4,6 +4,7 @@
 #![allow(dead_code)]
 
 use support::IScan;
+use support::ScanKind;
 
 #[derive(Clone, Copy)]
 pub struct Scan {
@@ -12,9 +13,9 @@ pub struct Scan {
 
 impl Scan {
     /// An unimplemented default
-    pub fn process<T, const N: usize>(
+    pub fn process<T, O, const N: usize>(
         &self,
-        _identity: T,
+        _kind: ScanKind,
         _v_in: &[T],
         _v_out: &mut [T],
     ) -> Result<(), String> {
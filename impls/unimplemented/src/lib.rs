@@ -4,6 +4,7 @@
 #![allow(dead_code)]
 
 use support::IScan;
+use support::ScanKind;
 
 #[derive(Clone, Copy)]
 pub struct Scan {
@@ -12,7 +13,23 @@ pub struct Scan {
 
 impl Scan {
     /// An unimplemented default
-    pub fn process<T>(&self, _identity: T, _v_in: &[T], _v_out: &mut [T]) -> Result<(), String> {
+    pub fn process<T, O>(
+        &self,
+        _kind: ScanKind,
+        _v_in: &[T],
+        _v_out: &mut [T],
+    ) -> Result<(), String> {
+        Err("Unimplemented".to_string())
+    }
+
+    /// An unimplemented default
+    pub fn process_segmented<T, O>(
+        &self,
+        _kind: ScanKind,
+        _flags: &[bool],
+        _v_in: &[T],
+        _v_out: &mut [T],
+    ) -> Result<(), String> {
         Err("Unimplemented".to_string())
     }
 }
@@ -0,0 +1,191 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use support::OperatorKind;
+
+/// The directory of the golden vector files (relative to the manifest directory).
+const VECTORS_DIR: &str = "vectors";
+
+/// The element types of the golden vectors, and whether they are floating point types.
+const TYPES: [(&str, bool); 12] = [
+    ("i8", false),
+    ("i16", false),
+    ("i32", false),
+    ("i64", false),
+    ("isize", false),
+    ("u8", false),
+    ("u16", false),
+    ("u32", false),
+    ("u64", false),
+    ("usize", false),
+    ("f32", true),
+    ("f64", true),
+];
+
+/// The header of a golden vector file (see `test_scan::golden`).
+struct Vector {
+    name: String,
+    path: String,
+    op: &'static str,
+    element: String,
+    is_float: bool,
+    kind: &'static str,
+}
+
+/// Parse the header of the golden vector file `path`, checking that the input and expected
+/// output vectors have the same (non-zero) length.
+fn parse_vector(path: &Path) -> Result<Vector, String> {
+    let name = path.file_stem().and_then(|s| s.to_str()).ok_or(format!(
+        "Invalid golden vector file name: {}",
+        path.display()
+    ))?;
+    let text = fs::read_to_string(path).or(Err(format!(
+        "Failed to read golden vector file: {}",
+        path.display()
+    )))?;
+    let get = |key: &str| -> Result<&str, String> {
+        text.lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .find(|(k, _)| k.trim() == key)
+            .map(|(_, v)| v.trim())
+            .ok_or(format!("Expected key '{}' in golden vector {}", key, name))
+    };
+    let op = match OperatorKind::from_str(get("op")?)? {
+        OperatorKind::Add => "OpAdd",
+        OperatorKind::Mul => "OpMul",
+        OperatorKind::Min => "OpMin",
+        OperatorKind::Max => "OpMax",
+        OperatorKind::BAnd => "OpBAnd",
+        OperatorKind::BOr => "OpBOr",
+        OperatorKind::BXor => "OpBXor",
+    };
+    let element = get("type")?;
+    let Some(&(_, is_float)) = TYPES.iter().find(|(t, _)| *t == element) else {
+        return Err(format!(
+            "Invalid element type in golden vector {}: {}",
+            name, element
+        ));
+    };
+    let kind = match get("kind")? {
+        "exclusive" => "Exclusive",
+        "inclusive" => "Inclusive",
+        s => {
+            return Err(format!(
+                "Invalid scan kind in golden vector {}: {}",
+                name, s
+            ))
+        }
+    };
+    let n_in = get("in")?.split_whitespace().count();
+    let n_out = get("out")?.split_whitespace().count();
+    if n_in == 0 || n_in != n_out {
+        return Err(format!(
+            "Expected non-empty input and output of the same length in golden vector {} \
+            (found {} and {})",
+            name, n_in, n_out
+        ));
+    }
+    Ok(Vector {
+        name: name.to_string(),
+        path: path.display().to_string(),
+        op,
+        element: element.to_string(),
+        is_float,
+        kind,
+    })
+}
+
+/// Returns the test function of the golden vector, calling the scan helper `$scan` of the test
+/// module (e.g., `scan::<T, O>(kind)`, with the number of lanes `$N` for SIMD and parallel
+/// implementations).
+fn to_test(vector: &Vector) -> String {
+    format!(
+        "        #[test]
+        fn test_{}() {{
+            $crate::golden::test_golden::<{}, _>(
+                $impl_str,
+                super::$scan::<{}, ::support::{} $(, $N)?>(::support::ScanKind::{}),
+                include_str!({:?}),
+            );
+        }}
+",
+        vector.name, vector.element, vector.element, vector.op, vector.kind, vector.path
+    )
+}
+
+/// Returns the constant of the contents of the golden vector, named after its file (e.g., `N8_1`
+/// for `n8_1.txt`), for the tests reading its input and expected output (see `get_in`).
+fn to_const(vector: &Vector) -> String {
+    format!(
+        "/// The golden vector file `{}.txt`, of the {} `{}` scan of `{}` elements.
+pub const {}: &str = include_str!({:?});
+",
+        vector.name,
+        vector.kind.to_lowercase(),
+        vector.op,
+        vector.element,
+        vector.name.to_uppercase(),
+        vector.path
+    )
+}
+
+/// Returns the exported macro `$macro_name` generating a module `$mod_name` of test functions for
+/// the golden vectors.
+fn to_macro(macro_name: &str, mod_name: &str, vectors: &[&Vector]) -> String {
+    let tests: Vec<String> = vectors.iter().map(|v| to_test(v)).collect();
+    format!(
+        "#[macro_export]
+macro_rules! {} {{
+    ($impl_str:expr, $scan:ident $(, $N:literal)?) => {{
+        mod {} {{
+{}        }}
+    }};
+}}
+",
+        macro_name,
+        mod_name,
+        tests.join("\n")
+    )
+}
+
+fn main() -> Result<(), String> {
+    let dir =
+        Path::new(&env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?).join(VECTORS_DIR);
+    println!("cargo::rerun-if-changed={}", dir.display());
+    let mut paths: Vec<_> = fs::read_dir(&dir)
+        .or(Err(format!(
+            "Failed to read golden vector directory: {}",
+            dir.display()
+        )))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    let vectors = paths
+        .iter()
+        .map(|path| parse_vector(path))
+        .collect::<Result<Vec<Vector>, String>>()?;
+    let vectors_int: Vec<&Vector> = vectors.iter().filter(|v| !v.is_float).collect();
+    let vectors_float: Vec<&Vector> = vectors.iter().filter(|v| v.is_float).collect();
+    let out = [
+        to_macro("golden_tests_int", "golden_int", &vectors_int),
+        to_macro("golden_tests_float", "golden_float", &vectors_float),
+        vectors
+            .iter()
+            .map(to_const)
+            .collect::<Vec<String>>()
+            .join("\n"),
+    ]
+    .join("\n");
+    let path_out = Path::new(&env::var("OUT_DIR").map_err(|e| e.to_string())?).join("golden.rs");
+    fs::write(&path_out, out).or(Err(format!(
+        "Failed to write golden tests: {}",
+        path_out.display()
+    )))
+}
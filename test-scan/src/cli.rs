@@ -0,0 +1,237 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::fmt;
+use std::io::Write;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+use std::str;
+
+/// Run the binary `bin` over the input vector `v` (passed as command line arguments).
+fn run_test<T: fmt::Display + fmt::Debug>(
+    bin: &str,
+    impl_id: i8,
+    args: &[&str],
+    v: &[T],
+) -> Result<String, String> {
+    let output = Command::new(bin)
+        .args(args)
+        .arg(impl_id.to_string())
+        .arg(v.len().to_string())
+        .args(v.iter().map(|x| x.to_string()))
+        .output()
+        .or(Err(format!("Failed to run test for v={:?}", v)))?;
+    get_result(output)
+}
+
+/// Run the test with the input vector written to stdin of the binary (e.g., for `--input=-`).
+fn run_test_input(bin: &str, impl_id: i8, args: &[&str], data: &[u8]) -> Result<Vec<u8>, String> {
+    let str_id = impl_id.to_string();
+    run_command(bin, &[args, &[str_id.as_str()]].concat(), data)
+}
+
+/// Run the binary with the command line arguments `args`, and `data` written to its stdin.
+fn run_command(bin: &str, args: &[&str], data: &[u8]) -> Result<Vec<u8>, String> {
+    let output = run_command_output(bin, args, data)?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        let stderr: &[u8] = output.stderr.as_slice();
+        Err(str::from_utf8(stderr).unwrap_or_default().to_string())
+    }
+}
+
+fn run_command_output(bin: &str, args: &[&str], data: &[u8]) -> Result<Output, String> {
+    let mut child = Command::new(bin)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .or(Err("Failed to run test for input data".to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(data)
+            .or(Err("Failed to write input data to stdin".to_string()))?;
+    }
+    child
+        .wait_with_output()
+        .or(Err("Failed to run test for input data".to_string()))
+}
+
+fn get_result(output: Output) -> Result<String, String> {
+    if output.status.success() {
+        let stdout: &[u8] = output.stdout.as_slice();
+        Ok(str::from_utf8(stdout).unwrap_or_default().to_string())
+    } else {
+        let stderr: &[u8] = output.stderr.as_slice();
+        Err(str::from_utf8(stderr).unwrap_or_default().to_string())
+    }
+}
+
+pub fn test_body<T>(bin: &str, impl_id: i8, impl_str: &str, v_in: &[T], v_out: &[T])
+where
+    T: fmt::Debug + fmt::Display,
+{
+    test_body_args(bin, impl_id, impl_str, &[], v_in, v_out)
+}
+
+/// Run the test with additional command line options (e.g., `--op=max`) passed to the binary.
+/// The element type of the vectors should match the `--type` option (default: `i64`).
+pub fn test_body_args<T>(
+    bin: &str,
+    impl_id: i8,
+    impl_str: &str,
+    args: &[&str],
+    v_in: &[T],
+    v_out: &[T],
+) where
+    T: fmt::Debug + fmt::Display,
+{
+    check_output(impl_str, args, run_test(bin, impl_id, args, v_in), v_out)
+}
+
+/// Run the test, checking that the output (e.g., of `--format=json`) contains each of the
+/// `expected` strings.
+pub fn test_body_contains<T>(
+    bin: &str,
+    impl_id: i8,
+    impl_str: &str,
+    args: &[&str],
+    v_in: &[T],
+    expected: &[&str],
+) where
+    T: fmt::Debug + fmt::Display,
+{
+    let res = match run_test(bin, impl_id, args, v_in) {
+        Ok(s) => s,
+        Err(m) => {
+            eprintln!("{}", m);
+            panic!()
+        }
+    };
+    eprintln!(":: TEST ({}) {}", impl_str, args.join(" "));
+    eprintln!("Output:\n{}", res);
+    eprintln!("Expected:\n{}", expected.join("\n"));
+    eprintln!();
+    assert!(expected.iter().all(|s| res.contains(s)))
+}
+
+/// Run the binary with the command line arguments `args` (e.g., of the `bench` subcommand),
+/// checking that the output contains each of the `expected` strings.
+pub fn test_body_command(bin: &str, args: &[&str], expected: &[&str]) {
+    let res = match run_command(bin, args, &[]) {
+        Ok(stdout) => str::from_utf8(&stdout).unwrap_or_default().to_string(),
+        Err(m) => {
+            eprintln!("{}", m);
+            panic!()
+        }
+    };
+    eprintln!(":: TEST {}", args.join(" "));
+    eprintln!("Output:\n{}", res);
+    eprintln!("Expected:\n{}", expected.join("\n"));
+    eprintln!();
+    assert!(expected.iter().all(|s| res.contains(s)))
+}
+
+/// Run the binary with the arguments, expecting it to fail with an output (on stdout or stderr)
+/// containing all of the expected strings.
+pub fn test_body_command_error(bin: &str, args: &[&str], expected: &[&str]) {
+    let res = match run_command_output(bin, args, &[]) {
+        Ok(output) if !output.status.success() => [output.stdout, output.stderr]
+            .map(|b| str::from_utf8(&b).unwrap_or_default().to_string())
+            .join("\n"),
+        Ok(_) => {
+            eprintln!("Expected failure of the command");
+            panic!()
+        }
+        Err(m) => {
+            eprintln!("{}", m);
+            panic!()
+        }
+    };
+    eprintln!(":: TEST {}", args.join(" "));
+    eprintln!("Output:\n{}", res);
+    eprintln!("Expected:\n{}", expected.join("\n"));
+    eprintln!();
+    assert!(expected.iter().all(|s| res.contains(s)))
+}
+
+/// Run the test with the input data written to stdin of the binary, for tests of the `--input`
+/// option (e.g., `--input=-`, or `--input=<path>` ignoring stdin).
+pub fn test_body_input<T>(
+    bin: &str,
+    impl_id: i8,
+    impl_str: &str,
+    args: &[&str],
+    data: &[u8],
+    v_out: &[T],
+) where
+    T: fmt::Debug + fmt::Display,
+{
+    let result = run_test_input(bin, impl_id, args, data)
+        .map(|stdout| str::from_utf8(&stdout).unwrap_or_default().to_string());
+    check_output(impl_str, args, result, v_out)
+}
+
+/// Run the test with the input data written to stdin of the binary, comparing the raw bytes
+/// written to stdout (e.g., for `--output=-`) with `data_out`.
+pub fn test_body_input_bytes(
+    bin: &str,
+    impl_id: i8,
+    impl_str: &str,
+    args: &[&str],
+    data: &[u8],
+    data_out: &[u8],
+) {
+    let res = match run_test_input(bin, impl_id, args, data) {
+        Ok(stdout) => stdout,
+        Err(m) => {
+            eprintln!("{}", m);
+            panic!()
+        }
+    };
+    eprintln!(":: TEST ({}) {}", impl_str, args.join(" "));
+    eprintln!("Output:\n{:?}", res);
+    eprintln!("Expected:\n{:?}", data_out);
+    eprintln!();
+    assert_eq!(res, data_out)
+}
+
+/// Returns the contents of a `.npy` file (format version 1.0) of the 1-D array of `n` elements
+/// of the NumPy dtype `descr` (e.g., `<i8`), whose little-endian bytes are `data`.
+pub fn gen_npy(descr: &str, n: usize, data: &[u8]) -> Vec<u8> {
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({},), }}",
+        descr, n
+    );
+    while (10 + header.len() + 1) % 64 != 0 {
+        header.push(' ');
+    }
+    header.push('\n');
+    let mut bytes: Vec<u8> = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(header.as_bytes());
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+fn check_output<T>(impl_str: &str, args: &[&str], result: Result<String, String>, v_out: &[T])
+where
+    T: fmt::Debug + fmt::Display,
+{
+    let res = match result {
+        Ok(s) => s,
+        Err(m) => {
+            eprintln!("{}", m);
+            panic!()
+        }
+    };
+    let str_out = format!("out : {:?}", v_out);
+    eprintln!(":: TEST ({}) {}", impl_str, args.join(" "));
+    eprintln!("Output:\n{}", res);
+    eprintln!("Expected:\n{}", str_out);
+    eprintln!();
+    assert!(res.contains(&str_out))
+}
@@ -0,0 +1,95 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::any;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::test_body;
+
+// NOTE: Generated by the build script, with one test function and one constant per golden vector
+// file
+include!(concat!(env!("OUT_DIR"), "/golden.rs"));
+
+/// Generate a module of test functions for the golden vectors (see `vectors/*.txt`) of all element
+/// types, calling the scan helper `$scan` of the enclosing test module as
+/// `$scan::<T, O $(, $N)?>(kind)`. Implementations of integer element types only should use
+/// `golden_tests_int` instead.
+#[macro_export]
+macro_rules! golden_tests {
+    ($impl_str:expr, $scan:ident $(, $N:literal)?) => {
+        $crate::golden_tests_int!($impl_str, $scan $(, $N)?);
+        $crate::golden_tests_float!($impl_str, $scan $(, $N)?);
+    };
+}
+
+/// Returns the value of the key `key` (as in `key = value`) of the golden vector `vector`.
+fn get_value<'a>(vector: &'a str, key: &str) -> Result<&'a str, String> {
+    vector
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.trim())
+        .ok_or(format!("Expected key '{}' in golden vector", key))
+}
+
+/// Returns the elements of the key `key` of the golden vector `vector`.
+fn get_elements<T: FromStr>(vector: &str, key: &str) -> Result<Vec<T>, String> {
+    get_value(vector, key)?
+        .split_whitespace()
+        .map(|s| {
+            T::from_str(s).or(Err(format!(
+                "Failed to parse element '{}' of key '{}' in golden vector",
+                s, key
+            )))
+        })
+        .collect()
+}
+
+/// Returns the elements of the key `key` of the golden vector `vector`, checking that `T` is the
+/// element type of the golden vector.
+fn get_typed_elements<T: FromStr>(vector: &str, key: &str) -> Vec<T> {
+    let elements = get_value(vector, "type").and_then(|element| {
+        if element == any::type_name::<T>() {
+            get_elements(vector, key)
+        } else {
+            Err(format!(
+                "Expected element type {} in golden vector (found {})",
+                any::type_name::<T>(),
+                element
+            ))
+        }
+    });
+    match elements {
+        Ok(v) => v,
+        Err(m) => {
+            eprintln!("{}", m);
+            panic!()
+        }
+    }
+}
+
+/// Returns the input vector of the golden vector file contents `vector` (e.g., `N8_1`).
+pub fn get_in<T: FromStr>(vector: &str) -> Vec<T> {
+    get_typed_elements(vector, "in")
+}
+
+/// Returns the expected output vector of the golden vector file contents `vector`.
+pub fn get_out<T: FromStr>(vector: &str) -> Vec<T> {
+    get_typed_elements(vector, "out")
+}
+
+/// Run the scan `f` in-process over the input vector of the golden vector file contents
+/// `vector`, comparing its output with the expected output (see `test_body`). The operator,
+/// element type and scan kind of the file are applied by the caller (see `golden_tests`).
+/// A golden vector file has one `key = value` line per key (`op`, `type`, `kind`, `in` and
+/// `out`), with the elements of `in` and `out` separated by whitespace, and comment lines
+/// starting with `#`.
+pub fn test_golden<T, F>(impl_str: &str, f: F, vector: &str)
+where
+    T: Copy + fmt::Debug + FromStr + PartialEq,
+    F: FnMut(&[T], &mut [T]) -> Result<(), String>,
+{
+    test_body(impl_str, f, &get_in(vector), &get_out(vector))
+}
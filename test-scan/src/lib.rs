@@ -1,130 +1,213 @@
 // Copyright 2025, Giordano Salvador
 // SPDX-License-Identifier: BSD-3-Clause
 
-use std::env;
-use std::fmt;
-use std::path::PathBuf;
-use std::process::Command;
-use std::str;
+pub mod cli;
+pub mod golden;
+pub mod prop;
 
-const BIN: &str = "scan";
+use std::fmt;
 
-fn get_command() -> Command {
-    let os_name = get_os();
-    let shell = get_shell(&os_name);
-    Command::new(&shell)
+/// Returns the input vector `[0, 1, .., 16, 0, 1, ..]` of length `n`, for tests of inputs
+/// too long to be spelled out as constants.
+pub fn gen_ramp_in(n: usize) -> Vec<i64> {
+    (0..n).map(|k| (k % 17) as i64).collect()
 }
 
-fn get_os() -> String {
-    String::from(if cfg!(target_os = "linux") {
-        "linux"
-    } else if cfg!(target_os = "macos") {
-        "macos"
-    } else if cfg!(target_os = "windows") {
-        "windows"
-    } else {
-        ""
-    })
+/// Returns the exclusive (sum) scan of `gen_ramp_in(n)`.
+pub fn gen_ramp_out(n: usize) -> Vec<i64> {
+    gen_ramp_in(n)
+        .iter()
+        .scan(0, |acc, &x| {
+            let y = *acc;
+            *acc += x;
+            Some(y)
+        })
+        .collect()
 }
 
-fn get_root_path() -> Result<String, String> {
-    match env::var("CARGO_MANIFEST_DIR") {
-        Ok(p) => {
-            let mut p_parent = PathBuf::from(p.as_str());
-            assert!(p_parent.pop());
-            assert!(p_parent.pop());
-            Ok(p_parent
-                .as_os_str()
-                .to_str()
-                .unwrap_or_default()
-                .to_string())
-        }
-        Err(e) => Err(e.to_string()),
-    }
+/// Returns `n` samples of a noisy sensor signal around a large offset, whose prefix sums lose
+/// precision when accumulated in `f32`.
+pub fn gen_sensor_in(n: usize) -> Vec<f32> {
+    // NOTE: Linear congruential generator (Numerical Recipes), for reproducible noise
+    let mut state: u32 = 12345;
+    (0..n)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            1000.0 + ((state >> 8) as f32 / (1 << 24) as f32 - 0.5) * 0.01
+        })
+        .collect()
 }
 
-fn get_shell(os_name: &str) -> String {
-    String::from(match os_name {
-        "linux" => "bash",
-        "macos" => "bash",
-        "windows" => "cmd",
-        _ => {
-            eprintln!("Unexpected target_os");
-            panic!()
-        }
-    })
+/// Returns the inclusive (sum) scan of `v` accumulated in `f64`, as a high-precision reference
+/// for scans in `f32`.
+pub fn gen_sensor_ref(v: &[f32]) -> Vec<f64> {
+    v.iter()
+        .scan(0.0, |acc, &x| {
+            *acc += x as f64;
+            Some(*acc)
+        })
+        .collect()
 }
 
-fn run_test<T: fmt::Display + fmt::Debug>(impl_id: i8, v: &[T]) -> Result<String, String> {
-    let str_n = v.len().to_string();
-    let str_v = to_string_vec(v);
-    let root_path = get_root_path()?;
-    let str_in = format!(
-        "{}/target/debug/{} {} {} {}",
-        root_path,
-        BIN,
-        impl_id,
-        str_n,
-        str_v.join(" ")
-    );
-    let output = get_command()
-        .arg("-c")
-        .arg(str_in)
-        .output()
-        .or(Err(format!("Failed to run test for v={:?}", v)))?;
-    if output.status.success() {
-        let stdout: &[u8] = output.stdout.as_slice();
-        Ok(str::from_utf8(stdout).unwrap_or_default().to_string())
-    } else {
-        let stderr: &[u8] = output.stderr.as_slice();
-        Err(str::from_utf8(stderr).unwrap_or_default().to_string())
-    }
+/// Returns the maximum error of `v` relative to the high-precision reference `v_ref`.
+pub fn get_max_rel_error(v: &[f32], v_ref: &[f64]) -> f64 {
+    v.iter()
+        .zip(v_ref.iter())
+        .map(|(&x, &y)| ((x as f64) - y).abs() / y.abs())
+        .fold(0.0, f64::max)
 }
 
-fn to_string_vec<T: fmt::Display>(v: &[T]) -> Vec<String> {
-    v.iter().map(|x| x.to_string()).collect()
+/// Run the scan `f` in-process over the input vector `v_in`, comparing its output with `v_out`.
+pub fn test_body<T, F>(impl_str: &str, f: F, v_in: &[T], v_out: &[T])
+where
+    T: Copy + fmt::Debug + PartialEq,
+    F: FnMut(&[T], &mut [T]) -> Result<(), String>,
+{
+    test_body_repeat(impl_str, 1, f, v_in, v_out)
 }
 
-pub fn test_body(impl_id: i8, impl_str: &str, v_in: &[i64], v_out: &[i64]) {
-    let res = match run_test(impl_id, v_in) {
-        Ok(s) => s,
-        Err(m) => {
+/// Run the scan `f` (e.g., reusing the same scan object) `n_repeat` times over the input vector
+/// `v_in`, comparing the output of each invocation with `v_out`. As in the driver, the input and
+/// output vectors are aligned to the size of `u64` (see `support::alloc_aligned`).
+pub fn test_body_repeat<T, F>(impl_str: &str, n_repeat: usize, mut f: F, v_in: &[T], v_out: &[T])
+where
+    T: Copy + fmt::Debug + PartialEq,
+    F: FnMut(&[T], &mut [T]) -> Result<(), String>,
+{
+    let n = v_in.len();
+    let Some(&def) = v_in.first() else {
+        eprintln!("Expected non-empty input vector");
+        panic!()
+    };
+    let (buf_in, _backing_store_in) = support::alloc_aligned::<T, u64>(n, def);
+    let (buf_out, _backing_store_out) = support::alloc_aligned::<T, u64>(n, def);
+    buf_in.copy_from_slice(v_in);
+    eprintln!(":: TEST ({}) n={} repeat={}", impl_str, n, n_repeat);
+    for _ in 0..n_repeat {
+        if let Err(m) = f(buf_in, buf_out) {
             eprintln!("{}", m);
             panic!()
         }
-    };
-    let str_out = format!("out : {:?}", v_out);
-    eprintln!(":: TEST ({})", impl_str);
-    eprintln!("Output:\n{}", res);
-    eprintln!("Expected:\n{}", str_out);
-    eprintln!();
-    assert!(res.contains(&str_out))
+        assert_eq!(buf_out, v_out);
+    }
 }
 
-pub const N8_1_IN: [i64; 8] = [3, 1, 7, 0, 4, 1, 6, 3];
-pub const N8_1_OUT: [i64; 8] = [0, 3, 4, 11, 11, 15, 16, 22];
-
-pub const N15_1_IN: [i64; 15] = [18, 12, 18, 0, 19, 10, 7, 17, 0, 1, 8, 17, 18, 17, 9];
-pub const N15_1_OUT: [i64; 15] = [
-    0, 18, 30, 48, 48, 67, 77, 84, 101, 101, 102, 110, 127, 145, 162,
-];
+/// Generate the scan helpers `$f` (any of `scan`, `scan_threads` and `scan_segmented`) of a test
+/// module, each returning the scan of a new scan object of type `$Scan`. The helpers take the
+/// generic parameters and bounds of the `process` functions of the implementation family
+/// `$family` (one of `seq`, `par_cpu`, `par_gpu` and `simd`), e.g., `scan::<T, O, N>(kind)` for
+/// the parallel CPU implementations.
+#[macro_export]
+macro_rules! scan_fns {
+    (seq, $Scan:ty; $($f:ident),+ $(,)?) => {
+        $(
+            $crate::scan_fns!(
+                @$f $Scan,
+                [T, O],
+                [T, O],
+                {
+                    T: Copy + ::support::IDisplay,
+                    O: ::support::IOperator<T>,
+                }
+            );
+        )+
+    };
+    (par_cpu, $Scan:ty; $($f:ident),+ $(,)?) => {
+        $(
+            $crate::scan_fns!(
+                @$f $Scan,
+                [T, O, const N: usize],
+                [T, O, N],
+                {
+                    T: Copy + ::support::IDisplay + Send + Sync,
+                    O: ::support::IOperator<T>,
+                }
+            );
+        )+
+    };
+    (par_gpu, $Scan:ty; $($f:ident),+ $(,)?) => {
+        $(
+            $crate::scan_fns!(
+                @$f $Scan,
+                [T, O, const N: usize],
+                [T, O, N],
+                {
+                    T: Copy
+                        + ::support::IBottom
+                        + ::support::ICast<i64>
+                        + ::support::IDisplay
+                        + ::support::IZero
+                        + ::bytemuck::Pod
+                        + Send,
+                    O: ::support::IOperator<T>,
+                    i64: ::support::ICast<T>,
+                }
+            );
+        )+
+    };
+    (simd, $Scan:ty; $($f:ident),+ $(,)?) => {
+        $(
+            $crate::scan_fns!(
+                @$f $Scan,
+                [T, O, const N: usize],
+                [T, O, N],
+                {
+                    T: Copy + ::support::IDisplay + ::std::simd::SimdElement,
+                    T::Mask: ::support::IDisplay + ::std::simd::MaskElement,
+                    O: ::support::IOperatorSimd<T, N>,
+                    ::std::simd::LaneCount<N>: ::std::simd::SupportedLaneCount,
+                }
+            );
+        )+
+    };
+    (@scan $Scan:ty, [$($gen:tt)*], [$($arg:tt)*], {$($bounds:tt)*}) => {
+        /// Returns the scan of a new scan object (as run by the driver).
+        fn scan<$($gen)*>(
+            kind: ::support::ScanKind,
+        ) -> impl FnMut(&[T], &mut [T]) -> Result<(), String>
+        where
+            $($bounds)*
+        {
+            let scan_obj = <$Scan as ::support::IScan>::new(false);
+            move |v_in, v_out| scan_obj.process::<$($arg)*>(kind, v_in, v_out)
+        }
+    };
+    (@scan_threads $Scan:ty, [$($gen:tt)*], [$($arg:tt)*], {$($bounds:tt)*}) => {
+        /// Returns the scan of a new scan object with `num_threads` worker threads.
+        fn scan_threads<$($gen)*>(
+            kind: ::support::ScanKind,
+            num_threads: usize,
+        ) -> impl FnMut(&[T], &mut [T]) -> Result<(), String>
+        where
+            $($bounds)*
+        {
+            let scan_obj = <$Scan>::with_num_threads(false, num_threads);
+            move |v_in, v_out| scan_obj.process::<$($arg)*>(kind, v_in, v_out)
+        }
+    };
+    (@scan_segmented $Scan:ty, [$($gen:tt)*], [$($arg:tt)*], {$($bounds:tt)*}) => {
+        /// Returns the segmented scan of a new scan object, restarting at the head flags `flags`.
+        fn scan_segmented<$($gen)*>(
+            kind: ::support::ScanKind,
+            flags: &[bool],
+        ) -> impl FnMut(&[T], &mut [T]) -> Result<(), String> + '_
+        where
+            $($bounds)*
+        {
+            let scan_obj = <$Scan as ::support::IScan>::new(false);
+            move |v_in, v_out| scan_obj.process_segmented::<$($arg)*>(kind, flags, v_in, v_out)
+        }
+    };
+}
 
 pub const N16_1_IN: [i64; 16] = [2, 2, 4, 8, 15, 12, 4, 19, 8, 11, 15, 12, 9, 17, 14, 15];
-pub const N16_1_OUT: [i64; 16] = [
-    0, 2, 4, 8, 16, 31, 43, 47, 66, 74, 85, 100, 112, 121, 138, 152,
-];
 
-pub const N100_1_IN: [i64; 100] = [
-    0, 13, 6, 18, 19, 9, 3, 8, 2, 6, 12, 13, 7, 2, 9, 17, 8, 9, 0, 14, 5, 18, 10, 12, 5, 16, 2, 10,
-    5, 5, 13, 8, 12, 18, 1, 3, 2, 10, 13, 9, 11, 19, 2, 2, 18, 12, 2, 9, 14, 9, 0, 8, 14, 15, 16,
-    2, 7, 2, 2, 15, 13, 3, 11, 16, 7, 15, 15, 20, 1, 10, 18, 13, 1, 4, 18, 8, 19, 3, 8, 20, 3, 9,
-    14, 4, 20, 11, 0, 8, 6, 4, 3, 19, 3, 18, 13, 0, 2, 13, 11, 11,
-];
-pub const N100_1_OUT: [i64; 100] = [
-    0, 0, 13, 19, 37, 56, 65, 68, 76, 78, 84, 96, 109, 116, 118, 127, 144, 152, 161, 161, 175, 180,
-    198, 208, 220, 225, 241, 243, 253, 258, 263, 276, 284, 296, 314, 315, 318, 320, 330, 343, 352,
-    363, 382, 384, 386, 404, 416, 418, 427, 441, 450, 450, 458, 472, 487, 503, 505, 512, 514, 516,
-    531, 544, 547, 558, 574, 581, 596, 611, 631, 632, 642, 660, 673, 674, 678, 696, 704, 723, 726,
-    734, 754, 757, 766, 780, 784, 804, 815, 815, 823, 829, 833, 836, 855, 858, 876, 889, 889, 891,
-    904, 915,
+pub const N16_1_SEG_FLAGS: &str = "--flags=1,0,0,1,1,0,0,0,0,1,0,0,0,0,0,1";
+pub const N16_1_SEG_OFFSETS: &str = "--offsets=0,3,4,9,15";
+pub const N16_1_SEG_HEADS: [bool; 16] = [
+    true, false, false, true, true, false, false, false, false, true, false, false, false, false,
+    false, true,
 ];
+pub const N16_1_SEG_OUT: [i64; 16] = [0, 2, 4, 0, 0, 15, 27, 31, 50, 0, 11, 26, 38, 47, 64, 0];
+pub const N16_1_SEG_INCL_OUT: [i64; 16] =
+    [2, 4, 8, 8, 15, 27, 31, 50, 58, 11, 26, 38, 47, 64, 78, 15];
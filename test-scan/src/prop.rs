@@ -0,0 +1,356 @@
+// Copyright 2025, Giordano Salvador
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::env;
+use std::fmt;
+
+use support::ICast;
+use support::IOperator;
+use support::Rng;
+
+/// The default seed of the random lengths and values of the property-based tests.
+pub const PROP_SEED: u64 = 0x5CA7_2025;
+
+/// The environment variable overriding the seed of the property-based tests (in decimal or
+/// `0x`-prefixed hexadecimal), e.g., to reproduce a failure or to explore other inputs.
+pub const PROP_SEED_VAR: &str = "PROP_SEED";
+
+/// The number of lanes of the property-based tests of implementations without SIMD lanes (resp.
+/// elements per chunk or tile).
+pub const PROP_LANES: usize = 4;
+
+/// The bound of the random values (in `[-PROP_MAX, PROP_MAX]`, cast to the element type), such
+/// that values repeat (e.g., as ties of `OpMin` and `OpMax`) and the prefix sums of a failing
+/// test stay readable.
+const PROP_MAX: i64 = 1000;
+
+/// Generate a module of property-based tests, calling the scan helper `$scan` of the enclosing
+/// test module as `$scan::<T, O $(, $N)?>(kind)` (see `golden_tests`), with the number of lanes
+/// `$N` (or `PROP_LANES`) for the lengths of the random inputs. The tests cover the sum over
+/// `i64` and `u32`, and the operators `OpMin`, `OpMax` and `OpBXor`.
+#[macro_export]
+macro_rules! prop_tests {
+    ($impl_str:expr, $scan:ident $(, $N:literal)?) => {
+        mod prop {
+            use ::support::OpAdd;
+            use ::support::OpBXor;
+            use ::support::OpMax;
+            use ::support::OpMin;
+            use ::support::ScanKind;
+            use $crate::prop::test_prop_fold;
+            use $crate::prop::test_prop_inclusive;
+            use $crate::prop::test_prop_linear;
+
+            const LANES: usize = $crate::prop_tests!(@lanes $($N)?);
+
+            #[test]
+            fn test_fold_add_i64() {
+                test_prop_fold::<i64, OpAdd, _, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<i64, OpAdd $(, $N)?>(ScanKind::Exclusive),
+                    super::$scan::<i64, OpAdd $(, $N)?>(ScanKind::Inclusive),
+                );
+            }
+
+            #[test]
+            fn test_fold_add_u32() {
+                test_prop_fold::<u32, OpAdd, _, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<u32, OpAdd $(, $N)?>(ScanKind::Exclusive),
+                    super::$scan::<u32, OpAdd $(, $N)?>(ScanKind::Inclusive),
+                );
+            }
+
+            #[test]
+            fn test_fold_max_i64() {
+                test_prop_fold::<i64, OpMax, _, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<i64, OpMax $(, $N)?>(ScanKind::Exclusive),
+                    super::$scan::<i64, OpMax $(, $N)?>(ScanKind::Inclusive),
+                );
+            }
+
+            #[test]
+            fn test_fold_xor_u32() {
+                test_prop_fold::<u32, OpBXor, _, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<u32, OpBXor $(, $N)?>(ScanKind::Exclusive),
+                    super::$scan::<u32, OpBXor $(, $N)?>(ScanKind::Inclusive),
+                );
+            }
+
+            #[test]
+            fn test_inclusive_add_i64() {
+                test_prop_inclusive::<i64, OpAdd, _, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<i64, OpAdd $(, $N)?>(ScanKind::Exclusive),
+                    super::$scan::<i64, OpAdd $(, $N)?>(ScanKind::Inclusive),
+                );
+            }
+
+            #[test]
+            fn test_inclusive_min_u32() {
+                test_prop_inclusive::<u32, OpMin, _, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<u32, OpMin $(, $N)?>(ScanKind::Exclusive),
+                    super::$scan::<u32, OpMin $(, $N)?>(ScanKind::Inclusive),
+                );
+            }
+
+            #[test]
+            fn test_linear_add_i64() {
+                test_prop_linear::<i64, OpAdd, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<i64, OpAdd $(, $N)?>(ScanKind::Exclusive),
+                );
+            }
+
+            #[test]
+            fn test_linear_xor_u32() {
+                test_prop_linear::<u32, OpBXor, _>(
+                    $impl_str,
+                    LANES,
+                    super::$scan::<u32, OpBXor $(, $N)?>(ScanKind::Exclusive),
+                );
+            }
+        }
+    };
+    (@lanes) => {
+        $crate::prop::PROP_LANES
+    };
+    (@lanes $N:literal) => {
+        $N
+    };
+}
+
+/// Returns the seed of the property-based tests, read from the environment variable
+/// `PROP_SEED_VAR` if set, or `PROP_SEED`.
+pub fn get_prop_seed() -> u64 {
+    let Ok(s) = env::var(PROP_SEED_VAR) else {
+        return PROP_SEED;
+    };
+    let seed = match s.strip_prefix("0x") {
+        Some(s_hex) => u64::from_str_radix(s_hex, 16),
+        None => s.parse::<u64>(),
+    };
+    match seed {
+        Ok(seed) => seed,
+        Err(_) => {
+            eprintln!("Invalid seed in {}: {}", PROP_SEED_VAR, s);
+            panic!()
+        }
+    }
+}
+
+/// Returns the lengths of the random inputs for `lanes` SIMD lanes (resp. elements per chunk or
+/// tile): the smallest lengths, the powers of two and their neighbours, some non-multiples of the
+/// lane width, and some random lengths.
+pub fn gen_prop_lengths(rng: &mut Rng, lanes: usize) -> Vec<usize> {
+    let mut v: Vec<usize> = vec![1, 2];
+    for k in 2..=12 {
+        v.extend([(1 << k) - 1, 1 << k, (1 << k) + 1]);
+    }
+    for r in [1, lanes / 2 + 1, lanes - 1] {
+        if 0 < r && r < lanes {
+            v.extend([lanes + r, 7 * lanes + r, 129 * lanes + r]);
+        }
+    }
+    v.extend((0..8).map(|_| 1 + rng.next_below(5000) as usize));
+    v.sort_unstable();
+    v.dedup();
+    v
+}
+
+/// Returns a random input vector of length `n` (see `PROP_MAX`).
+pub fn gen_prop_values<T>(rng: &mut Rng, n: usize) -> Vec<T>
+where
+    i64: ICast<T>,
+{
+    (0..n)
+        .map(|_| (rng.next_below(2 * PROP_MAX as u64 + 1) as i64 - PROP_MAX).cast())
+        .collect()
+}
+
+/// Returns the reference exclusive scan of `v`, as a sequential fold.
+fn get_fold_excl<T, O>(v: &[T]) -> Vec<T>
+where
+    T: Copy,
+    O: IOperator<T>,
+{
+    v.iter()
+        .scan(O::identity(), |acc, &x| {
+            let y = *acc;
+            *acc = O::combine(*acc, x);
+            Some(y)
+        })
+        .collect()
+}
+
+/// Returns the reference inclusive scan of `v`, as a sequential fold.
+fn get_fold_incl<T, O>(v: &[T]) -> Vec<T>
+where
+    T: Copy,
+    O: IOperator<T>,
+{
+    v.iter()
+        .scan(O::identity(), |acc, &x| {
+            *acc = O::combine(*acc, x);
+            Some(*acc)
+        })
+        .collect()
+}
+
+/// Returns the elementwise combination of `v_a` and `v_b`.
+fn get_combined<T, O>(v_a: &[T], v_b: &[T]) -> Vec<T>
+where
+    T: Copy,
+    O: IOperator<T>,
+{
+    v_a.iter()
+        .zip(v_b.iter())
+        .map(|(&a, &b)| O::combine(a, b))
+        .collect()
+}
+
+/// Run the scan `f` over the input vector `v_in`, with the input and output vectors aligned as
+/// in the driver (see `test_body_repeat`).
+fn run_scan<T, F>(impl_str: &str, f: &mut F, v_in: &[T], seed: u64) -> Vec<T>
+where
+    T: Copy,
+    F: FnMut(&[T], &mut [T]) -> Result<(), String>,
+{
+    let n = v_in.len();
+    let (buf_in, _backing_store_in) = support::alloc_aligned::<T, u64>(n, v_in[0]);
+    let (buf_out, _backing_store_out) = support::alloc_aligned::<T, u64>(n, v_in[0]);
+    buf_in.copy_from_slice(v_in);
+    if let Err(m) = f(buf_in, buf_out) {
+        eprintln!(
+            "{} (n={}, {}={:#x}): {}",
+            impl_str, n, PROP_SEED_VAR, seed, m
+        );
+        panic!()
+    }
+    buf_out.to_vec()
+}
+
+/// Check the exclusive and inclusive scans `f_excl` and `f_incl` against the reference fold,
+/// over random inputs of the lengths of `gen_prop_lengths`.
+pub fn test_prop_fold<T, O, FE, FI>(impl_str: &str, lanes: usize, mut f_excl: FE, mut f_incl: FI)
+where
+    T: Copy + fmt::Debug + PartialEq,
+    O: IOperator<T>,
+    FE: FnMut(&[T], &mut [T]) -> Result<(), String>,
+    FI: FnMut(&[T], &mut [T]) -> Result<(), String>,
+    i64: ICast<T>,
+{
+    let seed = get_prop_seed();
+    let mut rng = Rng::new(seed);
+    eprintln!(":: TEST ({}) prop=fold seed={:#x}", impl_str, seed);
+    for n in gen_prop_lengths(&mut rng, lanes) {
+        let v_in = gen_prop_values::<T>(&mut rng, n);
+        let v_excl = run_scan(impl_str, &mut f_excl, &v_in, seed);
+        assert_eq!(
+            v_excl,
+            get_fold_excl::<T, O>(&v_in),
+            "exclusive scan (n={}, {}={:#x})",
+            n,
+            PROP_SEED_VAR,
+            seed
+        );
+        let v_incl = run_scan(impl_str, &mut f_incl, &v_in, seed);
+        assert_eq!(
+            v_incl,
+            get_fold_incl::<T, O>(&v_in),
+            "inclusive scan (n={}, {}={:#x})",
+            n,
+            PROP_SEED_VAR,
+            seed
+        );
+    }
+}
+
+/// Check that the exclusive and inclusive scans `f_excl` and `f_incl` agree, i.e., that each
+/// inclusive prefix is the exclusive prefix combined with the input element, and the next
+/// exclusive prefix, over random inputs of the lengths of `gen_prop_lengths`.
+pub fn test_prop_inclusive<T, O, FE, FI>(
+    impl_str: &str,
+    lanes: usize,
+    mut f_excl: FE,
+    mut f_incl: FI,
+) where
+    T: Copy + fmt::Debug + PartialEq,
+    O: IOperator<T>,
+    FE: FnMut(&[T], &mut [T]) -> Result<(), String>,
+    FI: FnMut(&[T], &mut [T]) -> Result<(), String>,
+    i64: ICast<T>,
+{
+    let seed = get_prop_seed();
+    let mut rng = Rng::new(seed);
+    eprintln!(":: TEST ({}) prop=inclusive seed={:#x}", impl_str, seed);
+    for n in gen_prop_lengths(&mut rng, lanes) {
+        let v_in = gen_prop_values::<T>(&mut rng, n);
+        let v_excl = run_scan(impl_str, &mut f_excl, &v_in, seed);
+        let v_incl = run_scan(impl_str, &mut f_incl, &v_in, seed);
+        assert_eq!(
+            v_excl[0],
+            O::identity(),
+            "exclusive scan identity (n={}, {}={:#x})",
+            n,
+            PROP_SEED_VAR,
+            seed
+        );
+        assert_eq!(
+            v_incl,
+            get_combined::<T, O>(&v_excl, &v_in),
+            "inclusive scan (n={}, {}={:#x})",
+            n,
+            PROP_SEED_VAR,
+            seed
+        );
+        assert_eq!(
+            v_excl[1..],
+            v_incl[..n - 1],
+            "exclusive scan (n={}, {}={:#x})",
+            n,
+            PROP_SEED_VAR,
+            seed
+        );
+    }
+}
+
+/// Check that the scan `f` of a commutative operator distributes over the elementwise
+/// combination of its inputs, i.e., that `scan(a + b) = scan(a) + scan(b)` for the sum, over
+/// random inputs of the lengths of `gen_prop_lengths`.
+pub fn test_prop_linear<T, O, F>(impl_str: &str, lanes: usize, mut f: F)
+where
+    T: Copy + fmt::Debug + PartialEq,
+    O: IOperator<T>,
+    F: FnMut(&[T], &mut [T]) -> Result<(), String>,
+    i64: ICast<T>,
+{
+    let seed = get_prop_seed();
+    let mut rng = Rng::new(seed);
+    eprintln!(":: TEST ({}) prop=linear seed={:#x}", impl_str, seed);
+    for n in gen_prop_lengths(&mut rng, lanes) {
+        let v_a = gen_prop_values::<T>(&mut rng, n);
+        let v_b = gen_prop_values::<T>(&mut rng, n);
+        let v_out_a = run_scan(impl_str, &mut f, &v_a, seed);
+        let v_out_b = run_scan(impl_str, &mut f, &v_b, seed);
+        let v_out_ab = run_scan(impl_str, &mut f, &get_combined::<T, O>(&v_a, &v_b), seed);
+        assert_eq!(
+            v_out_ab,
+            get_combined::<T, O>(&v_out_a, &v_out_b),
+            "scan(combine(a, b)) (n={}, {}={:#x})",
+            n,
+            PROP_SEED_VAR,
+            seed
+        );
+    }
+}